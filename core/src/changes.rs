@@ -0,0 +1,74 @@
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::rule::{ALIVE, DEAD};
+
+/// Cells born, dying and dead during an update of the universe
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Changes<C: Coordinate = i32> {
+    births: Vec<Point2<C>>,
+    decays: Vec<(Point2<C>, u8)>,
+    deaths: Vec<Point2<C>>,
+}
+
+impl<C: Coordinate> Changes<C> {
+    /// Creates an empty change set
+    #[inline]
+    pub fn new() -> Changes<C> {
+        Changes {
+            births: Vec::new(),
+            decays: Vec::new(),
+            deaths: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn births(&self) -> &[Point2<C>] {
+        &self.births
+    }
+
+    /// Cells entering a dying state, with their new state
+    #[inline]
+    pub fn decays(&self) -> &[(Point2<C>, u8)] {
+        &self.decays
+    }
+
+    #[inline]
+    pub fn deaths(&self) -> &[Point2<C>] {
+        &self.deaths
+    }
+
+    /// Records change of given cell to given state
+    pub fn record(&mut self, cell: Point2<C>, state: u8) {
+        match state {
+            DEAD => self.death(cell),
+            ALIVE => self.birth(cell),
+            _ => self.decay(cell, state),
+        }
+    }
+
+    /// Records birth of given cell
+    #[inline]
+    pub fn birth(&mut self, cell: Point2<C>) {
+        self.births.push(cell);
+    }
+
+    /// Records decay of given cell to given dying state
+    #[inline]
+    pub fn decay(&mut self, cell: Point2<C>, state: u8) {
+        self.decays.push((cell, state));
+    }
+
+    /// Records death of given cell
+    #[inline]
+    pub fn death(&mut self, cell: Point2<C>) {
+        self.deaths.push(cell);
+    }
+}
+
+// Utils
+impl<C: Coordinate> Default for Changes<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::str::FromStr;
+use std::ops::{BitAnd, BitXor, Not};
+use na::{point, ClosedAdd, ClosedSub, Point2, Scalar, Vector2};
+
+/// Integer type usable as cell coordinate.
+/// Space spans over all values of the type: cells beyond its limits are considered as a dead border.
+pub trait Coordinate: Scalar + Copy + Ord + Hash + Debug + Display + FromStr + ClosedAdd + ClosedSub
+    + BitAnd<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+    const ZERO: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    /// Number of bits of the type
+    const BITS: u32;
+
+    /// Converts from i32, which always fits
+    fn from_i32(value: i32) -> Self;
+
+    /// Converts to i32, truncating value if it does not fit
+    fn as_i32(self) -> i32;
+
+    /// Converts from unsigned bits (two's complement), truncating value if it does not fit
+    fn from_bits(bits: u64) -> Self;
+
+    /// Returns bits of value as unsigned (two's complement)
+    fn to_bits(self) -> u64;
+
+    /// Mask keeping all bits from given one (included) to the highest one
+    fn high_mask(bit: u32) -> Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// Absolute difference, which always fits in an u64
+    fn abs_diff(self, rhs: Self) -> u64;
+
+    fn rem_euclid(self, rhs: Self) -> Self;
+
+    fn div_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_coordinate {
+    ($type:ty, $unsigned:ty) => {
+        impl Coordinate for $type {
+            const ZERO: Self = 0;
+            const MIN: Self = <$type>::MIN;
+            const MAX: Self = <$type>::MAX;
+            const BITS: u32 = <$type>::BITS;
+
+            #[inline]
+            fn from_i32(value: i32) -> Self {
+                value as $type
+            }
+
+            #[inline]
+            fn as_i32(self) -> i32 {
+                self as i32
+            }
+
+            #[inline]
+            fn from_bits(bits: u64) -> Self {
+                bits as $unsigned as $type
+            }
+
+            #[inline]
+            fn to_bits(self) -> u64 {
+                self as $unsigned as u64
+            }
+
+            #[inline]
+            fn high_mask(bit: u32) -> Self {
+                (<$unsigned>::MAX << bit) as $type
+            }
+
+            #[inline]
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$type>::checked_add(self, rhs)
+            }
+
+            #[inline]
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$type>::checked_sub(self, rhs)
+            }
+
+            #[inline]
+            fn abs_diff(self, rhs: Self) -> u64 {
+                <$type>::abs_diff(self, rhs) as u64
+            }
+
+            #[inline]
+            fn rem_euclid(self, rhs: Self) -> Self {
+                <$type>::rem_euclid(self, rhs)
+            }
+
+            #[inline]
+            fn div_euclid(self, rhs: Self) -> Self {
+                <$type>::div_euclid(self, rhs)
+            }
+        }
+    };
+}
+
+impl_coordinate!(i32, u32);
+impl_coordinate!(i64, u64);
+
+// Utils
+/// Moves point by given offset, returning None if result lies beyond coordinate limits
+pub fn checked_offset<C: Coordinate>(point: &Point2<C>, offset: &Vector2<i32>) -> Option<Point2<C>> {
+    Some(point![
+        point.x.checked_add(C::from_i32(offset.x))?,
+        point.y.checked_add(C::from_i32(offset.y))?
+    ])
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use na::vector;
+    use super::*;
+
+    #[test]
+    fn test_checked_offset() {
+        assert_eq!(checked_offset(&point![1, 2], &vector![-1, 1]), Some(point![0, 3]));
+        assert_eq!(checked_offset(&point![i32::MAX, 0], &vector![1, 0]), None);
+        assert_eq!(checked_offset(&point![0, i32::MIN], &vector![0, -1]), None);
+        assert_eq!(checked_offset(&point![i32::MAX as i64, 0], &vector![1, 0]), Some(point![1 << 31, 0]));
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(i32::high_mask(31), i32::MIN);
+        assert_eq!(i64::high_mask(2), -4);
+        assert_eq!((-1i32).to_bits(), u32::MAX as u64);
+        assert_eq!(i32::from_bits(1 << 31), i32::MIN);
+    }
+}
@@ -0,0 +1,396 @@
+use na::{point, Point2};
+use crate::hashlife::store::{ALIVE, DEAD, NodeId, NodeStore};
+use crate::neighborhood::Neighborhood;
+use crate::quadtree::arena::Arena;
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::global_node::GlobalNode;
+use crate::quadtree::GlobalQuadtree;
+use crate::quadtree::square_node::SquareNode;
+use crate::quadtree::tree::Tree;
+use crate::rule::Rule;
+
+mod store;
+
+/// Level of the node covering the whole global quadtree
+const GLOBAL_LEVEL: u32 = 32;
+
+/// Greatest step (as a power of 2) hashlife can compute at once
+pub const MAX_STEP: u32 = GLOBAL_LEVEL;
+
+/// Store size above which it is cleared before computing a new step
+const MAX_NODES: usize = 1 << 22;
+
+/// Hashlife engine, advancing patterns by 2^k generations at once
+#[derive(Clone, Debug)]
+pub struct Hashlife {
+    rule: Rule,
+    store: NodeStore,
+}
+
+impl Hashlife {
+    /// Creates an engine using given rule
+    pub fn new(rule: Rule) -> Hashlife {
+        Hashlife {
+            rule,
+            store: NodeStore::new(),
+        }
+    }
+
+    /// Changes rule, dropping results computed with the previous one
+    pub fn set_rule(&mut self, rule: Rule) {
+        if self.rule != rule {
+            self.rule = rule;
+            self.store.clear_results();
+        }
+    }
+
+    /// Advances given cells by 2^step generations, all cells being considered alive.
+    /// Cells leaving the global quadtree space are lost.
+    pub fn advance(&mut self, cells: &GlobalQuadtree<u8>, step: u32) -> Result<GlobalQuadtree<u8>, &'static str> {
+        if step > MAX_STEP {
+            return Err("hashlife cannot advance more than 2^32 generations at once");
+        }
+
+        if self.rule.states() > 2 {
+            return Err("hashlife only supports rules with 2 states");
+        }
+
+        if self.rule.neighborhood() != Neighborhood::moore() {
+            return Err("hashlife only supports rules with the range 1 moore neighborhood");
+        }
+
+        if self.store.len() > MAX_NODES {
+            self.store.clear();
+        }
+
+        // Pad twice so no cell can reach the border before the end of step
+        let root = self.import_global(cells);
+        let root = self.store.expand(root);
+        let root = self.store.expand(root);
+
+        let result = self.successor(root, step);
+        let result = self.store.inner(result);
+
+        Ok(self.export_global(result))
+    }
+
+    /// Computes center of node, advanced by 2^step generations (clamped to 2^(level - 2))
+    fn successor(&mut self, id: NodeId, step: u32) -> NodeId {
+        let level = self.store.level(id);
+        let step = step.min(level - 2);
+
+        if self.store.population(id) == 0 {
+            return self.store.empty(level - 1);
+        }
+
+        if let Some(result) = self.store.result(id, step) {
+            return result;
+        }
+
+        let result = if level == 2 {
+            self.evolve_base(id)
+        } else {
+            // 9 overlapping sub-nodes, advanced by up to half the step
+            let mut centers = [[DEAD; 3]; 3];
+
+            for (sx, row) in centers.iter_mut().enumerate() {
+                for (sy, center) in row.iter_mut().enumerate() {
+                    let sub = self.sub_node(id, sx, sy);
+                    *center = self.successor(sub, step);
+                }
+            }
+
+            let quarters = std::array::from_fn(|i| {
+                let (qx, qy) = (i >> 1, i & 1);
+                std::array::from_fn(|j| centers[qx + (j >> 1)][qy + (j & 1)])
+            });
+
+            let children = if step < level - 2 {
+                // Step already done, only keep centers
+                quarters.map(|quarter: [NodeId; 4]| {
+                    let inner = std::array::from_fn(|j| self.store.children(quarter[j])[j ^ 0b11]);
+                    self.store.join(inner)
+                })
+            } else {
+                quarters.map(|quarter| {
+                    let node = self.store.join(quarter);
+                    self.successor(node, step)
+                })
+            };
+
+            self.store.join(children)
+        };
+
+        self.store.set_result(id, step, result);
+        result
+    }
+
+    /// Computes next generation of the 2x2 center of a 4x4 node
+    fn evolve_base(&mut self, id: NodeId) -> NodeId {
+        let children = std::array::from_fn(|i| {
+            let (cx, cy) = ((i >> 1) + 1, (i & 1) + 1);
+            let neighborhood = Neighborhood::moore();
+
+            let neighbors = neighborhood.alive_neighbors(neighborhood.offsets().filter(|offset| {
+                let (gx, gy) = (cx as i32 + offset.x, cy as i32 + offset.y);
+                self.grandchild(id, gx as usize, gy as usize) == ALIVE
+            }));
+
+            let is_alive = self.grandchild(id, cx, cy) == ALIVE;
+
+            if self.rule.evolve(is_alive, neighbors) { ALIVE } else { DEAD }
+        });
+
+        self.store.join(children)
+    }
+
+    /// Returns grandchild of node, in its 4x4 grid
+    #[inline]
+    fn grandchild(&self, id: NodeId, gx: usize, gy: usize) -> NodeId {
+        let child = self.store.children(id)[(gx >> 1) << 1 | (gy >> 1)];
+        self.store.children(child)[(gx & 1) << 1 | (gy & 1)]
+    }
+
+    /// Returns sub-node of the lower level, anchored on given grandchild
+    fn sub_node(&mut self, id: NodeId, sx: usize, sy: usize) -> NodeId {
+        let children = std::array::from_fn(|i| self.grandchild(id, sx + (i >> 1), sy + (i & 1)));
+        self.store.join(children)
+    }
+
+    /// Builds node covering the whole global quadtree
+    fn import_global(&mut self, cells: &GlobalQuadtree<u8>) -> NodeId {
+        let half = 1i64 << (GLOBAL_LEVEL - 1);
+        let children = std::array::from_fn(|i| {
+            let anchor = point![((i >> 1) as i64 - 1) * half, ((i & 1) as i64 - 1) * half];
+            self.import(cells.nodes(), &cells.root().children[i], anchor, GLOBAL_LEVEL - 1)
+        });
+
+        self.store.join(children)
+    }
+
+    /// Builds node of given level, anchored at given point, holding tree
+    fn import(&mut self, nodes: &Arena<u8>, tree: &Tree<u8>, anchor: Point2<i64>, level: u32) -> NodeId {
+        let (target, size) = match tree {
+            Tree::Empty => return self.store.empty(level),
+            Tree::Leaf(pt, _) => (pt, 1),
+            &Tree::Node(id) => (&nodes[id].area.anchor, nodes[id].area.size),
+        };
+
+        if size == 1 << level {
+            return match tree {
+                &Tree::Node(id) => {
+                    let children = std::array::from_fn(|i| {
+                        let anchor = child_anchor(anchor, level, i);
+                        self.import(nodes, &nodes[id].children[i], anchor, level - 1)
+                    });
+
+                    self.store.join(children)
+                },
+                _ => ALIVE,
+            };
+        }
+
+        // Tree is smaller: place it in the matching quarter
+        let half = 1i64 << (level - 1);
+        let idx = ((target.x as i64 >= anchor.x + half) as usize) << 1 | (target.y as i64 >= anchor.y + half) as usize;
+
+        let children = std::array::from_fn(|i| {
+            if i == idx {
+                self.import(nodes, tree, child_anchor(anchor, level, i), level - 1)
+            } else {
+                self.store.empty(level - 1)
+            }
+        });
+
+        self.store.join(children)
+    }
+
+    /// Builds quadtree from node covering the whole global quadtree
+    fn export_global(&self, id: NodeId) -> GlobalQuadtree<u8> {
+        let half = 1i64 << (GLOBAL_LEVEL - 1);
+        let children = self.store.children(id);
+        let mut nodes = Arena::new();
+
+        let root = GlobalNode {
+            population: self.store.population(id) as usize,
+            children: std::array::from_fn(|i| {
+                let anchor = point![((i >> 1) as i64 - 1) * half, ((i & 1) as i64 - 1) * half];
+                self.export(&mut nodes, children[i], anchor)
+            }),
+        };
+
+        GlobalQuadtree::from_parts(root, nodes)
+    }
+
+    /// Builds tree holding given node's cells, storing its nodes in given arena
+    fn export(&self, nodes: &mut Arena<u8>, id: NodeId, anchor: Point2<i64>) -> Tree<u8> {
+        let level = self.store.level(id);
+
+        if self.store.population(id) == 0 {
+            return Tree::Empty;
+        }
+
+        if level == 0 {
+            return Tree::Leaf(point![anchor.x as i32, anchor.y as i32], 1);
+        }
+
+        let node_children = self.store.children(id);
+        let children: [Tree<u8>; 4] = std::array::from_fn(|i| {
+            self.export(nodes, node_children[i], child_anchor(anchor, level, i))
+        });
+
+        match children.iter().filter(|child| !child.is_empty()).count() {
+            1 => children.into_iter().find(|child| !child.is_empty()).unwrap(),
+            _ => Tree::Node(nodes.alloc(SquareNode {
+                area: BinarySquare {
+                    anchor: point![anchor.x as i32, anchor.y as i32],
+                    size: 1 << level,
+                },
+                population: self.store.population(id) as usize,
+                children,
+            })),
+        }
+    }
+}
+
+// Utils
+#[inline]
+fn child_anchor(anchor: Point2<i64>, level: u32, idx: usize) -> Point2<i64> {
+    let half = 1i64 << (level - 1);
+    point![anchor.x + (idx >> 1) as i64 * half, anchor.y + (idx & 1) as i64 * half]
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use na::point;
+    use super::*;
+
+    fn build(cells: &[Point2<i32>]) -> GlobalQuadtree<u8> {
+        let mut tree = GlobalQuadtree::new();
+        cells.iter().for_each(|&cell| { tree.insert(cell, 1); });
+
+        tree
+    }
+
+    fn collect(tree: &GlobalQuadtree<u8>) -> HashSet<Point2<i32>> {
+        tree.iter().map(|(&pt, _)| pt).collect()
+    }
+
+    fn naive_step(cells: &HashSet<Point2<i32>>, rule: &Rule) -> HashSet<Point2<i32>> {
+        let candidates = cells.iter()
+            .flat_map(|c| (-1..=1).flat_map(move |dx| (-1..=1).map(move |dy| point![c.x + dx, c.y + dy])))
+            .collect::<HashSet<_>>();
+
+        candidates.into_iter()
+            .filter(|c| {
+                let neighborhood = Neighborhood::moore();
+                let neighbors = neighborhood.alive_neighbors(neighborhood.offsets().filter(|d| cells.contains(&(c + d))));
+
+                rule.evolve(cells.contains(c), neighbors)
+            })
+            .collect()
+    }
+
+    fn assert_matches_naive_steps(rule: Rule) {
+        let soup = [
+            point![0, 0], point![1, 0], point![2, 0], point![4, 0], point![0, 1], point![3, 2], point![4, 2],
+            point![1, 3], point![2, 3], point![4, 3], point![0, 4], point![2, 4], point![4, 4], point![-1, 2],
+        ];
+        let mut hashlife = Hashlife::new(rule);
+
+        let mut expected = HashSet::from(soup);
+
+        for _ in 0..8 {
+            expected = naive_step(&expected, &rule);
+        }
+
+        let result = hashlife.advance(&build(&soup), 3).unwrap();
+        assert_eq!(collect(&result), expected);
+    }
+
+    #[test]
+    fn test_still_life() {
+        let block = build(&[point![0, 0], point![0, 1], point![1, 0], point![1, 1]]);
+        let mut hashlife = Hashlife::new(Rule::conway());
+
+        let result = hashlife.advance(&block, 5).unwrap();
+        assert_eq!(collect(&result), collect(&block));
+    }
+
+    #[test]
+    fn test_oscillator() {
+        let blinker = build(&[point![-1, 0], point![0, 0], point![1, 0]]);
+        let mut hashlife = Hashlife::new(Rule::conway());
+
+        let result = hashlife.advance(&blinker, 0).unwrap();
+        assert_eq!(collect(&result), HashSet::from([point![0, -1], point![0, 0], point![0, 1]]));
+
+        let result = hashlife.advance(&blinker, 1).unwrap();
+        assert_eq!(collect(&result), collect(&blinker));
+    }
+
+    #[test]
+    fn test_glider_crossing_quarters() {
+        let glider = [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]];
+        let start = glider.map(|pt| point![pt.x - 300, pt.y - 300]);
+        let mut hashlife = Hashlife::new(Rule::conway());
+
+        let result = hashlife.advance(&build(&start), 10).unwrap();
+        let expected = start.map(|pt| point![pt.x + 256, pt.y + 256]);
+
+        assert_eq!(collect(&result), HashSet::from(expected));
+    }
+
+    #[test]
+    fn test_matches_naive_steps() {
+        assert_matches_naive_steps("B36/S23".parse().unwrap());
+    }
+
+    #[test]
+    fn test_non_totalistic_matches_naive_steps() {
+        assert_matches_naive_steps("B2-a3/S12-k3".parse().unwrap());
+    }
+
+    #[test]
+    fn test_export_matches_inserted_tree() {
+        let cells = [point![3, 1], point![3, 3], point![1, 3], point![3, 5], point![-7, 12]];
+        let tree = build(&cells);
+
+        let mut hashlife = Hashlife::new(Rule::conway());
+        let root = hashlife.import_global(&tree);
+
+        assert_eq!(hashlife.export_global(root), tree);
+    }
+
+    #[test]
+    fn test_generations_rule() {
+        let mut hashlife = Hashlife::new("B2/S/C3".parse().unwrap());
+
+        assert_eq!(
+            hashlife.advance(&GlobalQuadtree::new(), 2).unwrap_err(),
+            "hashlife only supports rules with 2 states"
+        );
+    }
+
+    #[test]
+    fn test_larger_than_life_rule() {
+        let mut hashlife = Hashlife::new("R5,C0,M1,S34..58,B34..45,NM".parse().unwrap());
+
+        assert_eq!(
+            hashlife.advance(&GlobalQuadtree::new(), 2).unwrap_err(),
+            "hashlife only supports rules with the range 1 moore neighborhood"
+        );
+    }
+
+    #[test]
+    fn test_step_too_large() {
+        let mut hashlife = Hashlife::new(Rule::conway());
+
+        assert_eq!(
+            hashlife.advance(&GlobalQuadtree::new(), 33).unwrap_err(),
+            "hashlife cannot advance more than 2^32 generations at once"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+/// Index of a node inside a store
+pub type NodeId = u32;
+
+/// Dead cell (level 0 node)
+pub const DEAD: NodeId = 0;
+
+/// Alive cell (level 0 node)
+pub const ALIVE: NodeId = 1;
+
+/// Canonical hashlife node, covering a square of 2^level cells
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct HashNode {
+    level: u32,
+    population: u64,
+    children: [NodeId; 4],
+}
+
+/// Hash-consed node store, with memoized results
+#[derive(Clone, Debug)]
+pub struct NodeStore {
+    nodes: Vec<HashNode>,
+    index: HashMap<[NodeId; 4], NodeId>,
+    empty: Vec<NodeId>,
+    results: HashMap<(NodeId, u32), NodeId>,
+}
+
+impl NodeStore {
+    /// Creates a store holding only both cell nodes
+    pub fn new() -> NodeStore {
+        NodeStore {
+            nodes: vec![
+                HashNode { level: 0, population: 0, children: [DEAD; 4] },
+                HashNode { level: 0, population: 1, children: [DEAD; 4] },
+            ],
+            index: HashMap::new(),
+            empty: vec![DEAD],
+            results: HashMap::new(),
+        }
+    }
+
+    /// Number of nodes in store
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Drops every node and result
+    pub fn clear(&mut self) {
+        *self = NodeStore::new();
+    }
+
+    /// Drops every memoized result
+    #[inline]
+    pub fn clear_results(&mut self) {
+        self.results.clear();
+    }
+
+    #[inline]
+    pub fn level(&self, id: NodeId) -> u32 {
+        self.nodes[id as usize].level
+    }
+
+    #[inline]
+    pub fn population(&self, id: NodeId) -> u64 {
+        self.nodes[id as usize].population
+    }
+
+    #[inline]
+    pub fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.nodes[id as usize].children
+    }
+
+    /// Returns canonical node made of given children
+    pub fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+
+        let id = self.nodes.len() as NodeId;
+        let node = HashNode {
+            level: self.level(children[0]) + 1,
+            population: children.iter().map(|&child| self.population(child)).sum(),
+            children,
+        };
+
+        self.nodes.push(node);
+        self.index.insert(children, id);
+
+        id
+    }
+
+    /// Returns canonical empty node of given level
+    pub fn empty(&mut self, level: u32) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let last = self.empty[self.empty.len() - 1];
+            let upper = self.join([last; 4]);
+
+            self.empty.push(upper);
+        }
+
+        self.empty[level as usize]
+    }
+
+    /// Wraps node in an empty border, returning a node of the upper level with the same center
+    pub fn expand(&mut self, id: NodeId) -> NodeId {
+        let empty = self.empty(self.level(id) - 1);
+        let children = self.children(id);
+
+        let expanded = std::array::from_fn(|i| {
+            let mut grandchildren = [empty; 4];
+            grandchildren[i ^ 0b11] = children[i];
+
+            grandchildren
+        });
+
+        let expanded = expanded.map(|grandchildren| self.join(grandchildren));
+        self.join(expanded)
+    }
+
+    /// Returns center of node, at the lower level
+    pub fn inner(&mut self, id: NodeId) -> NodeId {
+        let children = self.children(id);
+        let inner = std::array::from_fn(|i| self.children(children[i])[i ^ 0b11]);
+
+        self.join(inner)
+    }
+
+    /// Memoized result of node, advanced by 2^step generations
+    #[inline]
+    pub fn result(&self, id: NodeId, step: u32) -> Option<NodeId> {
+        self.results.get(&(id, step)).copied()
+    }
+
+    #[inline]
+    pub fn set_result(&mut self, id: NodeId, step: u32, result: NodeId) {
+        self.results.insert((id, step), result);
+    }
+}
+
+// Utils
+impl Default for NodeStore {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_is_canonical() {
+        let mut store = NodeStore::new();
+
+        let a = store.join([DEAD, ALIVE, DEAD, ALIVE]);
+        let b = store.join([DEAD, ALIVE, DEAD, ALIVE]);
+
+        assert_eq!(a, b);
+        assert_eq!(store.level(a), 1);
+        assert_eq!(store.population(a), 2);
+        assert_eq!(store.len(), 3);
+    }
+
+    #[test]
+    fn test_expand_and_inner() {
+        let mut store = NodeStore::new();
+
+        let node = store.join([ALIVE, DEAD, DEAD, ALIVE]);
+        let expanded = store.expand(node);
+
+        assert_eq!(store.level(expanded), 2);
+        assert_eq!(store.population(expanded), 2);
+        assert_eq!(store.inner(expanded), node);
+    }
+}
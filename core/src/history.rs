@@ -0,0 +1,135 @@
+use std::collections::vec_deque::{Iter, VecDeque};
+
+/// Default number of statistics kept by history
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// Statistics of the universe after an update
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Statistics {
+    pub generation: u64,
+    pub population: usize,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+/// Bounded history of statistics, oldest ones are dropped once full
+#[derive(Clone, Debug)]
+pub struct History {
+    capacity: usize,
+    entries: VecDeque<Statistics>,
+}
+
+// Methods
+impl History {
+    /// Creates an empty history, holding at most capacity entries
+    #[inline]
+    pub fn new(capacity: usize) -> History {
+        History {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes capacity, dropping oldest entries if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+
+        if self.entries.len() > capacity {
+            self.entries.drain(..self.entries.len() - capacity);
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns most recent entry
+    #[inline]
+    pub fn last(&self) -> Option<&Statistics> {
+        self.entries.back()
+    }
+
+    /// Iterates on entries, from oldest to most recent
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, Statistics> {
+        self.entries.iter()
+    }
+
+    /// Adds an entry, dropping the oldest one if full
+    pub fn push(&mut self, statistics: Statistics) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(statistics);
+    }
+
+    /// Removes all entries
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// Utils
+impl Default for History {
+    #[inline]
+    fn default() -> Self {
+        History::new(DEFAULT_CAPACITY)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(generation: u64) -> Statistics {
+        Statistics { generation, ..Statistics::default() }
+    }
+
+    #[test]
+    fn test_push_drops_oldest() {
+        let mut history = History::new(3);
+
+        for generation in 0..5 {
+            history.push(entry(generation));
+        }
+
+        assert_eq!(history.iter().map(|s| s.generation).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(history.last(), Some(&entry(4)));
+    }
+
+    #[test]
+    fn test_set_capacity() {
+        let mut history = History::new(5);
+
+        for generation in 0..5 {
+            history.push(entry(generation));
+        }
+
+        history.set_capacity(2);
+
+        assert_eq!(history.iter().map(|s| s.generation).collect::<Vec<_>>(), [3, 4]);
+
+        history.set_capacity(0);
+        history.push(entry(5));
+
+        assert!(history.is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+extern crate nalgebra as na;
+extern crate pythagore as py;
+
+pub mod changes;
+pub mod coordinate;
+pub mod hashlife;
+pub mod history;
+pub mod neighborhood;
+pub mod pattern;
+pub mod quadtree;
+pub mod random;
+pub mod render;
+pub mod rule;
+pub mod snapshot;
+pub mod symmetry;
+pub mod topology;
+pub mod universe;
+pub mod update_list;
+mod utils;
+
+pub use changes::Changes;
+pub use coordinate::Coordinate;
+pub use history::{History, Statistics};
+pub use neighborhood::{Neighborhood, NeighborhoodShape, Neighbors};
+pub use pattern::{ParseError, Pattern};
+pub use quadtree::GlobalQuadtree;
+pub use random::Rng;
+pub use render::Palette;
+pub use rule::Rule;
+pub use snapshot::Snapshot;
+pub use symmetry::Symmetry;
+pub use topology::Topology;
+pub use universe::Universe;
+pub use update_list::{UpdateArea, UpdateList};
@@ -0,0 +1,197 @@
+use na::{vector, Vector2};
+
+/// Shape of a neighborhood
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NeighborhoodShape {
+    /// Square of cells around center
+    #[default]
+    Moore,
+    /// Diamond of cells around center (manhattan distance)
+    VonNeumann,
+    /// Disc of cells around center (euclidean distance)
+    Circular,
+}
+
+/// Cells considered as neighbors of a cell, within given range
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Neighborhood {
+    shape: NeighborhoodShape,
+    range: i32,
+}
+
+/// Alive neighbors of a cell
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Neighbors {
+    /// Number of alive neighbors
+    Count(usize),
+    /// Arrangement of alive neighbors in the classic 8 cells neighborhood, one bit per neighbor
+    /// (see [`configuration_bit`])
+    Configuration(u8),
+}
+
+/// Offsets of the 8 neighbors, from the highest configuration bit (NW) to the lowest (SE)
+const CONFIGURATION_OFFSETS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+// Methods
+impl Neighborhood {
+    /// Creates a neighborhood of given shape and range
+    #[inline]
+    pub fn new(shape: NeighborhoodShape, range: i32) -> Neighborhood {
+        Neighborhood { shape, range }
+    }
+
+    /// Classic 8 cells neighborhood
+    #[inline]
+    pub fn moore() -> Neighborhood {
+        Neighborhood::new(NeighborhoodShape::Moore, 1)
+    }
+
+    #[inline]
+    pub fn shape(&self) -> NeighborhoodShape {
+        self.shape
+    }
+
+    #[inline]
+    pub fn range(&self) -> i32 {
+        self.range
+    }
+
+    /// Test if given offset (from center) is part of the neighborhood.
+    /// Center itself is included.
+    pub fn contains(&self, offset: &Vector2<i32>) -> bool {
+        let (dx, dy) = (offset.x.abs(), offset.y.abs());
+
+        match self.shape {
+            NeighborhoodShape::Moore => dx <= self.range && dy <= self.range,
+            NeighborhoodShape::VonNeumann => dx + dy <= self.range,
+            NeighborhoodShape::Circular => dx * dx + dy * dy <= self.range * self.range + self.range,
+        }
+    }
+
+    /// Iterates over offsets of all neighbors, excluding center
+    pub fn offsets(&self) -> impl Iterator<Item = Vector2<i32>> {
+        let neighborhood = *self;
+        let range = self.range;
+
+        (-range..=range)
+            .flat_map(move |dx| (-range..=range).map(move |dy| vector![dx, dy]))
+            .filter(move |offset| *offset != Vector2::zeros() && neighborhood.contains(offset))
+    }
+
+    /// Number of neighbors, excluding center
+    pub fn size(&self) -> usize {
+        self.offsets().count()
+    }
+
+    /// Summarizes alive neighbors, from their offsets.
+    /// Classic 8 cells neighborhood gives their configuration, others only count them.
+    pub fn alive_neighbors(&self, alive: impl Iterator<Item = Vector2<i32>>) -> Neighbors {
+        if *self == Neighborhood::moore() {
+            Neighbors::Configuration(alive.fold(0, |configuration, offset| configuration | configuration_bit(&offset)))
+        } else {
+            Neighbors::Count(alive.count())
+        }
+    }
+}
+
+impl Neighbors {
+    /// Number of alive neighbors
+    #[inline]
+    pub fn count(&self) -> usize {
+        match *self {
+            Neighbors::Count(count) => count,
+            Neighbors::Configuration(configuration) => configuration.count_ones() as usize,
+        }
+    }
+}
+
+// Utils
+/// Returns bit matching given neighbor offset in configurations.
+/// Neighbors are ordered NW, N, NE, W, E, SW, S, SE from highest to lowest bit.
+pub fn configuration_bit(offset: &Vector2<i32>) -> u8 {
+    let idx = CONFIGURATION_OFFSETS.iter()
+        .position(|&(dx, dy)| offset.x == dx && offset.y == dy)
+        .expect("offset must be a neighbor in the classic 8 cells neighborhood");
+
+    1 << (7 - idx)
+}
+
+/// Returns the 8 configurations obtained by rotating and reflecting given one
+pub fn symmetries(configuration: u8) -> [u8; 8] {
+    std::array::from_fn(|i| {
+        let mut result = 0;
+
+        for (idx, &(dx, dy)) in CONFIGURATION_OFFSETS.iter().enumerate() {
+            if configuration & (1 << (7 - idx)) == 0 {
+                continue;
+            }
+
+            // Rotates i % 4 quarter turns, then reflects on x axis if i >= 4
+            let (mut x, mut y) = (dx, dy);
+
+            for _ in 0..i % 4 {
+                (x, y) = (-y, x);
+            }
+
+            if i >= 4 {
+                x = -x;
+            }
+
+            result |= configuration_bit(&vector![x, y]);
+        }
+
+        result
+    })
+}
+
+impl Default for Neighborhood {
+    #[inline]
+    fn default() -> Self {
+        Neighborhood::moore()
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        assert_eq!(Neighborhood::moore().size(), 8);
+        assert_eq!(Neighborhood::new(NeighborhoodShape::Moore, 5).size(), 120);
+        assert_eq!(Neighborhood::new(NeighborhoodShape::VonNeumann, 1).size(), 4);
+        assert_eq!(Neighborhood::new(NeighborhoodShape::VonNeumann, 2).size(), 12);
+        assert_eq!(Neighborhood::new(NeighborhoodShape::Circular, 1).size(), 8);
+        assert_eq!(Neighborhood::new(NeighborhoodShape::Circular, 2).size(), 20);
+    }
+
+    #[test]
+    fn test_contains() {
+        let neighborhood = Neighborhood::new(NeighborhoodShape::Circular, 3);
+
+        assert!(neighborhood.contains(&vector![0, 0]));
+        assert!(neighborhood.contains(&vector![3, 0]));
+        assert!(neighborhood.contains(&vector![2, -2]));
+        assert!(!neighborhood.contains(&vector![3, 2]));
+        assert!(!neighborhood.contains(&vector![-4, 0]));
+    }
+
+    #[test]
+    fn test_alive_neighbors() {
+        let alive = [vector![-1, -1], vector![1, 0], vector![1, 1]];
+
+        assert_eq!(Neighborhood::moore().alive_neighbors(alive.into_iter()), Neighbors::Configuration(0b1000_1001));
+        assert_eq!(Neighborhood::new(NeighborhoodShape::Moore, 2).alive_neighbors(alive.into_iter()), Neighbors::Count(3));
+    }
+
+    #[test]
+    fn test_symmetries() {
+        // NW corner turns to every corner
+        let mut corners = symmetries(0b1000_0000);
+        corners.sort();
+
+        assert_eq!(corners, [0b0000_0001, 0b0000_0001, 0b0000_0100, 0b0000_0100, 0b0010_0000, 0b0010_0000, 0b1000_0000, 0b1000_0000]);
+        assert_eq!(symmetries(0b0100_0010)[1], 0b0001_1000);
+    }
+}
@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::quadtree::GlobalQuadtree;
+use crate::rule::{ALIVE, Rule};
+use crate::topology::Topology;
+
+pub mod life106;
+pub mod macrocell;
+pub mod plaintext;
+pub mod rle;
+
+/// Greatest number of living cells in parsed patterns, as run lengths or macrocell nodes can describe huge patterns
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// Cells and metadata read from, or written to, a pattern file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pattern<C: Coordinate = i32> {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    pub rule: Rule,
+    pub topology: Topology,
+    pub cells: GlobalQuadtree<u8, C>,
+}
+
+/// Supported pattern file formats
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Run length encoded (.rle)
+    Rle,
+    /// Grid of '.' and 'O' (.cells)
+    Plaintext,
+    /// List of alive cells coordinates (.lif)
+    Life106,
+    /// Deduplicated quadtree nodes (.mc)
+    Macrocell,
+}
+
+impl Format {
+    /// Detects format of given pattern file, from its first meaningful line.
+    /// Defaults to RLE, as it is the most common one.
+    pub fn detect(input: &str) -> Format {
+        let line = input.lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or_default();
+
+        if line.starts_with(macrocell::HEADER) {
+            Format::Macrocell
+        } else if line.starts_with(life106::HEADER) {
+            Format::Life106
+        } else if line.starts_with('!') || line.chars().all(|c| matches!(c, '.' | 'O' | '*')) {
+            Format::Plaintext
+        } else if line.split_whitespace().count() == 2 && line.split_whitespace().all(|coord| coord.parse::<i64>().is_ok()) {
+            Format::Life106
+        } else {
+            Format::Rle
+        }
+    }
+}
+
+// Methods
+impl<C: Coordinate> Pattern<C> {
+    /// Parses pattern using given format
+    pub fn parse_as(input: &str, format: Format) -> Result<Pattern<C>, ParseError> {
+        match format {
+            Format::Rle => rle::parse(input),
+            Format::Plaintext => plaintext::parse(input),
+            Format::Life106 => life106::parse(input),
+            Format::Macrocell => macrocell::parse(input),
+        }
+    }
+
+    /// Writes pattern using given format.
+    /// Plaintext and Life 1.06 only hold alive cells, plaintext being limited to small patterns.
+    pub fn write(&self, format: Format) -> Result<String, &'static str> {
+        match format {
+            Format::Rle => Ok(rle::write(self)),
+            Format::Plaintext => plaintext::write(self),
+            Format::Life106 => Ok(life106::write(self)),
+            Format::Macrocell => Ok(macrocell::write(self)),
+        }
+    }
+}
+
+// Utils
+impl<C: Coordinate> FromStr for Pattern<C> {
+    type Err = ParseError;
+
+    /// Parses pattern, detecting its format
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pattern::parse_as(s, Format::detect(s))
+    }
+}
+
+impl<C: Coordinate> Default for Pattern<C> {
+    fn default() -> Self {
+        Pattern {
+            name: None,
+            author: None,
+            comments: Vec::new(),
+            rule: Rule::default(),
+            topology: Topology::default(),
+            cells: GlobalQuadtree::new(),
+        }
+    }
+}
+
+/// Error found while parsing a pattern file, at given position (both starting at 1)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: &'static str,
+}
+
+impl ParseError {
+    #[inline]
+    pub fn new(line: usize, column: usize, message: &'static str) -> ParseError {
+        ParseError { line, column, message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (line {}, column {})", self.message, self.line, self.column)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Alive cells of pattern, sorted by rows
+fn alive_cells<C: Coordinate>(pattern: &Pattern<C>) -> Vec<Point2<C>> {
+    let mut cells = pattern.cells.iter()
+        .filter(|&(_, &state)| state == ALIVE)
+        .map(|(&pt, _)| pt)
+        .collect::<Vec<_>>();
+
+    cells.sort_by_key(|pt| (pt.y, pt.x));
+    cells
+}
+
+/// Column of given part of line, which must be a slice of line, skipping its leading whitespaces
+fn column_of(line: &str, part: &str) -> usize {
+    let offset = part.as_ptr() as usize - line.as_ptr() as usize;
+    let skipped = part.len() - part.trim_start().len();
+
+    line[..offset + skipped].chars().count() + 1
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"), Format::Rle);
+        assert_eq!(Format::detect("x = 3, y = 3\nbo$2bo$3o!"), Format::Rle);
+        assert_eq!(Format::detect("!Name: Glider\n.O\n..O\nOOO"), Format::Plaintext);
+        assert_eq!(Format::detect("\n.O\n..O\nOOO"), Format::Plaintext);
+        assert_eq!(Format::detect("#Life 1.06\n0 -1\n1 0"), Format::Life106);
+        assert_eq!(Format::detect("0 -1\n1 0"), Format::Life106);
+        assert_eq!(Format::detect("[M2] (golly 2.0)\n#R B3/S23\n.*$..*$***$\n4 0 0 0 1"), Format::Macrocell);
+    }
+
+    #[test]
+    fn test_formats_round_trip() {
+        let glider = "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!".parse::<Pattern>().unwrap();
+
+        for format in [Format::Rle, Format::Plaintext, Format::Life106, Format::Macrocell] {
+            let written = glider.write(format).unwrap();
+
+            assert_eq!(Format::detect(&written), format);
+            assert_eq!(written.parse::<Pattern>().unwrap().cells, glider.cells);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use na::point;
+use crate::coordinate::Coordinate;
+use crate::pattern::{alive_cells, column_of, ParseError, Pattern};
+use crate::rule::ALIVE;
+
+/// Header of Life 1.06 files
+pub const HEADER: &str = "#Life 1.06";
+
+/// Parses a Life 1.06 pattern: a list of alive cells coordinates, one per line
+pub fn parse<C: Coordinate>(input: &str) -> Result<Pattern<C>, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with(HEADER) {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next().unwrap_or(' ');
+            let content = String::from(chars.as_str().trim());
+
+            match kind {
+                'N' => pattern.name = Some(content),
+                'O' => pattern.author = Some(content),
+                'D' | 'C' => pattern.comments.push(content),
+                _ => (),
+            }
+
+            continue;
+        }
+
+        let coords = line.split_whitespace().collect::<Vec<_>>();
+
+        match coords[..] {
+            [x, y] => {
+                let parse = |coord: &str| coord.parse::<C>()
+                    .map_err(|_| ParseError::new(idx + 1, column_of(line, coord), "cell coordinates must be integers within coordinate limits"));
+
+                cells.push((point![parse(x)?, parse(y)?], ALIVE));
+            },
+            _ => return Err(ParseError::new(idx + 1, column_of(line, line), "cells must be 2 integers separated by a space")),
+        }
+    }
+
+    pattern.cells = cells.into_iter().collect();
+
+    Ok(pattern)
+}
+
+/// Writes alive cells of pattern in Life 1.06 format
+pub fn write<C: Coordinate>(pattern: &Pattern<C>) -> String {
+    let mut output = format!("{HEADER}\n");
+
+    if let Some(name) = &pattern.name {
+        output += &format!("#N {name}\n");
+    }
+
+    if let Some(author) = &pattern.author {
+        output += &format!("#O {author}\n");
+    }
+
+    for comment in &pattern.comments {
+        output += &format!("#D {comment}\n");
+    }
+
+    for pt in alive_cells(pattern) {
+        output += &format!("{} {}\n", pt.x, pt.y);
+    }
+
+    output
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pattern = parse::<i64>("#Life 1.06\n#D Far glider\n1 -10000000000\n2 -9999999999\n0 -9999999998\n1 -9999999998\n2 -9999999998\n").unwrap();
+
+        assert_eq!(pattern.comments, ["Far glider"]);
+        assert_eq!(pattern.cells.population(), 5);
+        assert!(pattern.cells.has(&point![1, -10_000_000_000]));
+
+        assert_eq!(
+            parse::<i32>("#Life 1.06\n0 0\n  1 10000000000\n"),
+            Err(ParseError::new(3, 5, "cell coordinates must be integers within coordinate limits"))
+        );
+        assert_eq!(
+            parse::<i32>("#Life 1.06\n0 0 0\n"),
+            Err(ParseError::new(2, 1, "cells must be 2 integers separated by a space"))
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let input = "#Life 1.06\n#N Blinker\n#D Period 2\n-1 0\n0 0\n1 0\n";
+        let pattern = parse::<i32>(input).unwrap();
+
+        assert_eq!(write(&pattern), input);
+    }
+}
@@ -0,0 +1,542 @@
+use std::collections::HashMap;
+use na::{point, Point2};
+use crate::coordinate::Coordinate;
+use crate::pattern::{column_of, ParseError, Pattern, MAX_CELLS};
+use crate::quadtree::GlobalQuadtree;
+use crate::quadtree::arena::Arena;
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::global_node::GlobalNode;
+use crate::quadtree::quarter::global_quarter;
+use crate::quadtree::square_node::SquareNode;
+use crate::quadtree::tree::Tree;
+use crate::rule::{ALIVE, DEAD};
+use crate::topology::Topology;
+
+/// Header of Macrocell files
+pub const HEADER: &str = "[M2]";
+
+/// Level of 8x8 bitmap leaves, used by 2 states rules
+const BITMAP_LEVEL: u32 = 3;
+
+/// Level of 2x2 leaves holding states, used by multi-state rules
+const STATES_LEVEL: u32 = 1;
+
+/// Quarter indexes of children, in Macrocell order (nw, ne, sw, se)
+const QUARTERS: [usize; 4] = [0b00, 0b10, 0b01, 0b11];
+
+/// Parses a Macrocell pattern: deduplicated quadtree nodes, each one referencing previous ones.
+/// Last node is the root, centered on origin.
+pub fn parse<C: Coordinate>(input: &str) -> Result<Pattern<C>, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut nodes = Vec::new();
+    let mut root_line = 0;
+
+    for (idx, line) in input.lines().enumerate() {
+        let number = idx + 1;
+
+        if line.trim().is_empty() || line.starts_with(HEADER) {
+            continue;
+        }
+
+        if line.starts_with('#') {
+            parse_comment(line, number, &mut pattern)?;
+            continue;
+        }
+
+        let node = if line.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_node(line, number, &nodes, &pattern)?
+        } else {
+            parse_bitmap(line, number)?
+        };
+
+        nodes.push(node);
+        root_line = number;
+    }
+
+    if let Some(root) = nodes.last() {
+        let level = root.level();
+
+        if level > C::BITS {
+            return Err(ParseError::new(root_line, 1, "pattern exceeds coordinate limits"));
+        }
+
+        // Shared nodes can describe huge patterns in a few lines, but loaded trees hold each cell in its own place:
+        // population is checked before building them
+        let populations = populations(&nodes)
+            .filter(|populations| populations[nodes.len()] <= MAX_CELLS as u64)
+            .ok_or(ParseError::new(root_line, 1, "pattern has too many living cells"))?;
+
+        // Root quarters are anchored separately, as the biggest ones start beyond half the coordinate range
+        let half = 1 << (level - 1);
+        let corner = |bit: usize| if bit != 0 { C::ZERO } else { !C::from_bits(half - 1) };
+        let mut arena = Arena::new();
+        let mut children = [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty];
+
+        for idx in 0..4 {
+            let anchor = point![corner(idx & 0b01), corner(idx & 0b10)];
+            let offset = ((idx as u64 & 1) * half, (idx as u64 >> 1) * half);
+
+            children[global_quarter(&anchor) as usize] = match root {
+                MacrocellNode::Node(_, refs) => build(&nodes, &populations, refs[idx], (0, 0), half, anchor, &mut arena),
+                _ => build(&nodes, &populations, nodes.len(), offset, half, anchor, &mut arena),
+            };
+        }
+
+        let population = populations[nodes.len()] as usize;
+        pattern.cells = GlobalQuadtree::from_parts(GlobalNode { population, children }, arena);
+    }
+
+    Ok(pattern)
+}
+
+/// Writes pattern in Macrocell format.
+/// Identical nodes are written once, so output size depends on the pattern structure and not on its population.
+pub fn write<C: Coordinate>(pattern: &Pattern<C>) -> String {
+    let mut output = format!("{HEADER} (game-of-life)\n#R {}", pattern.rule);
+
+    if pattern.topology != Topology::Plane {
+        output += &format!(":{}", pattern.topology);
+    }
+
+    output.push('\n');
+
+    if let Some(name) = &pattern.name {
+        output += &format!("#N {name}\n");
+    }
+
+    if let Some(author) = &pattern.author {
+        output += &format!("#O {author}\n");
+    }
+
+    for comment in &pattern.comments {
+        output += &format!("#C {comment}\n");
+    }
+
+    let root = pattern.cells.root();
+    let leaf_level = if pattern.rule.states() > 2 { STATES_LEVEL } else { BITMAP_LEVEL };
+
+    // Smallest root, centered on origin, holding every quarter
+    let level = root.children.iter().enumerate()
+        .filter_map(|(quarter, tree)| quarter_level(tree, pattern.cells.nodes(), quarter))
+        .max();
+
+    if let Some(level) = level {
+        let level = level.max(leaf_level) + 1;
+        let half = 1 << (level - 1);
+        let corner = |bit: usize| if bit != 0 { C::ZERO } else { !C::from_bits(half - 1) };
+
+        let mut writer = Writer {
+            nodes: pattern.cells.nodes(),
+            leaf_level,
+            lines: Vec::new(),
+            index: HashMap::new(),
+        };
+
+        let children = QUARTERS.map(|quarter| {
+            let square = BinarySquare { anchor: point![corner(quarter & 0b10), corner(quarter & 0b01)], size: half };
+            writer.node(&root.children[quarter], &square)
+        });
+
+        writer.add(format!("{level} {} {} {} {}", children[0], children[1], children[2], children[3]));
+
+        for line in writer.lines {
+            output += &line;
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+// Utils
+/// Node read from a Macrocell file, children being 1-based references to previous nodes (0 meaning empty)
+enum MacrocellNode {
+    Bitmap(u64),
+    States([u8; 4]),
+    Node(u32, [usize; 4]),
+}
+
+impl MacrocellNode {
+    fn level(&self) -> u32 {
+        match self {
+            MacrocellNode::Bitmap(_) => BITMAP_LEVEL,
+            MacrocellNode::States(_) => STATES_LEVEL,
+            MacrocellNode::Node(level, _) => *level,
+        }
+    }
+}
+
+/// Deduplicates written nodes, using their line as key
+struct Writer<'a, C: Coordinate> {
+    nodes: &'a Arena<u8, C>,
+    leaf_level: u32,
+    lines: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl<'a, C: Coordinate> Writer<'a, C> {
+    /// Writes node covering square, holding given tree. Returns its reference
+    fn node(&mut self, tree: &'a Tree<u8, C>, square: &BinarySquare<C>) -> usize {
+        let nodes = self.nodes;
+
+        let inner = match tree {
+            Tree::Empty => return 0,
+            Tree::Leaf(pt, _) => pt,
+            &Tree::Node(id) => &nodes[id].area.anchor,
+        };
+
+        let level = square.size.trailing_zeros();
+
+        let line = if level == self.leaf_level {
+            self.leaf(tree, square)
+        } else {
+            let half = square.size >> 1;
+            let offset = |bit: usize| if bit != 0 { C::from_bits(half) } else { C::ZERO };
+
+            let children = QUARTERS.map(|quarter| {
+                let child = BinarySquare {
+                    anchor: point![square.anchor.x + offset(quarter & 0b10), square.anchor.y + offset(quarter & 0b01)],
+                    size: half,
+                };
+
+                match tree {
+                    &Tree::Node(id) if nodes[id].area.size == square.size => self.node(&nodes[id].children[quarter], &child),
+                    _ if square.quarter(inner) as usize == quarter => self.node(tree, &child),
+                    _ => 0,
+                }
+            });
+
+            format!("{level} {} {} {} {}", children[0], children[1], children[2], children[3])
+        };
+
+        self.add(line)
+    }
+
+    /// Builds leaf line, holding cells of tree
+    fn leaf(&self, tree: &'a Tree<u8, C>, square: &BinarySquare<C>) -> String {
+        let size = square.size as usize;
+        let mut grid = vec![DEAD; size * size];
+        let mut stack = vec![tree];
+
+        while let Some(tree) = stack.pop() {
+            match tree {
+                Tree::Empty => (),
+                Tree::Leaf(pt, state) => {
+                    let (x, y) = (pt.x.abs_diff(square.anchor.x) as usize, pt.y.abs_diff(square.anchor.y) as usize);
+                    grid[y * size + x] = *state;
+                },
+                &Tree::Node(id) => stack.extend(&self.nodes[id].children),
+            }
+        }
+
+        if self.leaf_level == STATES_LEVEL {
+            return format!("{STATES_LEVEL} {} {} {} {}", grid[0], grid[1], grid[2], grid[3]);
+        }
+
+        let rows = grid.chunks(size)
+            .map(|row| row.iter().map(|&state| if state == DEAD { '.' } else { '*' }).collect::<String>())
+            .map(|row| String::from(row.trim_end_matches('.')))
+            .collect::<Vec<_>>();
+
+        format!("{}$", rows.join("$").trim_end_matches('$'))
+    }
+
+    /// Adds line if not already written. Returns its reference
+    fn add(&mut self, line: String) -> usize {
+        if let Some(&id) = self.index.get(&line) {
+            return id;
+        }
+
+        self.lines.push(line.clone());
+        self.index.insert(line, self.lines.len());
+
+        self.lines.len()
+    }
+}
+
+/// Level of the root quarter needed to hold given tree, found in given global quarter
+fn quarter_level<C: Coordinate>(tree: &Tree<u8, C>, nodes: &Arena<u8, C>, quarter: usize) -> Option<u32> {
+    let (anchor, size) = match tree {
+        Tree::Empty => return None,
+        Tree::Leaf(pt, _) => (pt, 1),
+        &Tree::Node(id) => (&nodes[id].area.anchor, nodes[id].area.size),
+    };
+
+    // Root quarters all touch origin: compare with the cell next to it
+    let corner = |bit: usize| if bit != 0 { C::ZERO } else { !C::ZERO };
+    let spread = (anchor.x ^ corner(quarter & 0b10)).to_bits() | (anchor.y ^ corner(quarter & 0b01)).to_bits();
+
+    Some(size.trailing_zeros().max(u64::BITS - spread.leading_zeros()))
+}
+
+/// Computes population of each node, by reference (0 being the empty node).
+/// Returns None if a population overflows.
+fn populations(nodes: &[MacrocellNode]) -> Option<Vec<u64>> {
+    let mut populations = vec![0];
+
+    for node in nodes {
+        let population = match node {
+            MacrocellNode::Bitmap(bits) => bits.count_ones() as u64,
+            MacrocellNode::States(states) => states.iter().filter(|&&state| state != DEAD).count() as u64,
+            MacrocellNode::Node(_, children) => children.iter()
+                .try_fold(0u64, |sum, &child| sum.checked_add(populations[child]))?,
+        };
+
+        populations.push(population);
+    }
+
+    Some(populations)
+}
+
+/// Builds tree of given square of node, anchored at given point.
+/// Offset locates the square inside leaf nodes, shared nodes being built again for each reference.
+fn build<C: Coordinate>(nodes: &[MacrocellNode], populations: &[u64], reference: usize, offset: (u64, u64), size: u64, anchor: Point2<C>, arena: &mut Arena<u8, C>) -> Tree<u8, C> {
+    if populations[reference] == 0 {
+        return Tree::Empty;
+    }
+
+    let node = &nodes[reference - 1];
+
+    if size == 1 {
+        let state = match node {
+            MacrocellNode::Bitmap(bits) if bits & (1 << (offset.1 * 8 + offset.0)) != 0 => ALIVE,
+            MacrocellNode::States(states) => states[(offset.1 * 2 + offset.0) as usize],
+            _ => DEAD,
+        };
+
+        return if state != DEAD { Tree::Leaf(anchor, state) } else { Tree::Empty };
+    }
+
+    let half = size / 2;
+    let mut children = [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty];
+
+    for (idx, &quarter) in QUARTERS.iter().enumerate() {
+        let (x, y) = ((idx as u64 & 1) * half, (idx as u64 >> 1) * half);
+        let at = point![anchor.x + C::from_bits(x), anchor.y + C::from_bits(y)];
+
+        children[quarter] = match node {
+            MacrocellNode::Node(_, refs) => build(nodes, populations, refs[idx], (0, 0), half, at, arena),
+            _ => build(nodes, populations, reference, (offset.0 + x, offset.1 + y), half, at, arena),
+        };
+    }
+
+    join(BinarySquare { anchor, size }, children, arena)
+}
+
+/// Joins children of given square: squares with less than 2 non-empty children are replaced by their content
+fn join<C: Coordinate>(area: BinarySquare<C>, children: [Tree<u8, C>; 4], arena: &mut Arena<u8, C>) -> Tree<u8, C> {
+    let mut filled = children.iter().filter(|child| !child.is_empty());
+
+    match (filled.next(), filled.next()) {
+        (None, _) => Tree::Empty,
+        (Some(child), None) => child.clone(),
+        _ => {
+            let population = children.iter().map(|child| child.population(arena)).sum();
+            Tree::Node(arena.alloc(SquareNode { area, population, children }))
+        },
+    }
+}
+
+/// Parses '#' lines (rule, name, author and comments)
+fn parse_comment<C: Coordinate>(line: &str, number: usize, pattern: &mut Pattern<C>) -> Result<(), ParseError> {
+    let mut chars = line[1..].chars();
+    let kind = chars.next().unwrap_or(' ');
+    let content = chars.as_str().trim();
+
+    match kind {
+        'N' => pattern.name = Some(String::from(content)),
+        'O' => pattern.author = Some(String::from(content)),
+        'C' | 'D' => pattern.comments.push(String::from(content)),
+        'R' => {
+            let (rule, topology) = content.split_once(':').unwrap_or((content, ""));
+
+            pattern.rule = rule.trim().parse()
+                .map_err(|message| ParseError::new(number, column_of(line, rule), message))?;
+            pattern.topology = topology.trim().parse()
+                .map_err(|message| ParseError::new(number, column_of(line, topology), message))?;
+        },
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Parses 8x8 bitmap leaf, made of '.' and '*' rows ended by '$'
+fn parse_bitmap(line: &str, number: usize) -> Result<MacrocellNode, ParseError> {
+    let (mut x, mut y, mut bits) = (0, 0, 0u64);
+
+    for (col, c) in line.chars().enumerate() {
+        let error = |message| ParseError::new(number, col + 1, message);
+
+        match c {
+            '.' | '*' if x >= 8 || y >= 8 => return Err(error("leaves must be 8x8 bitmaps")),
+            '.' => x += 1,
+            '*' => {
+                bits |= 1 << (y * 8 + x);
+                x += 1;
+            },
+            '$' => {
+                x = 0;
+                y += 1;
+            },
+            c if c.is_whitespace() => (),
+            _ => return Err(error("unexpected character in pattern")),
+        }
+    }
+
+    Ok(MacrocellNode::Bitmap(bits))
+}
+
+/// Parses node line (<level> <nw> <ne> <sw> <se>). Children of level 1 nodes are cell states.
+fn parse_node<C: Coordinate>(line: &str, number: usize, nodes: &[MacrocellNode], pattern: &Pattern<C>) -> Result<MacrocellNode, ParseError> {
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+
+    let [level, nw, ne, sw, se] = parts[..] else {
+        return Err(ParseError::new(number, 1, "nodes must be formed as <level> <nw> <ne> <sw> <se>"));
+    };
+
+    let level = match level.parse::<u32>() {
+        Ok(level @ 1..=64) => level,
+        _ => return Err(ParseError::new(number, column_of(line, level), "node level must be between 1 and 64")),
+    };
+
+    let mut parsed = [0; 4];
+
+    for (child, part) in parsed.iter_mut().zip([nw, ne, sw, se]) {
+        let error = |message| ParseError::new(number, column_of(line, part), message);
+
+        *child = part.parse::<usize>().map_err(|_| error("node children must be positive integers"))?;
+
+        if level == STATES_LEVEL {
+            if *child >= pattern.rule.states() as usize {
+                return Err(error("cell state is not supported by rule"));
+            }
+        } else if *child > nodes.len() {
+            return Err(error("node children must reference previous nodes"));
+        } else if *child != 0 && nodes[*child - 1].level() != level - 1 {
+            return Err(error("node children must be one level below it"));
+        }
+    }
+
+    if level == STATES_LEVEL {
+        Ok(MacrocellNode::States(parsed.map(|state| state as u8)))
+    } else {
+        Ok(MacrocellNode::Node(level, parsed))
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use crate::rule::Rule;
+    use super::*;
+
+    const GLIDER: &str = "[M2] (golly 2.0)\n#R B3/S23\n.*$..*$***$\n4 0 0 0 1\n";
+
+    fn points<C: Coordinate>(pattern: &Pattern<C>) -> Vec<(Point2<C>, u8)> {
+        let mut cells = pattern.cells.iter().map(|(&pt, &state)| (pt, state)).collect::<Vec<_>>();
+        cells.sort_by_key(|(pt, _)| (pt.y, pt.x));
+        cells
+    }
+
+    #[test]
+    fn test_parse() {
+        let pattern = parse::<i32>(GLIDER).unwrap();
+
+        assert_eq!(pattern.rule, Rule::conway());
+        assert_eq!(
+            points(&pattern),
+            [(point![1, 0], ALIVE), (point![2, 1], ALIVE), (point![0, 2], ALIVE), (point![1, 2], ALIVE), (point![2, 2], ALIVE)]
+        );
+
+        // Root of level 5 is anchored at (-16, -16)
+        let pattern = parse::<i32>("[M2]\n*$\n4 1 0 0 0\n5 2 0 0 2\n").unwrap();
+        assert_eq!(points(&pattern), [(point![-16, -16], ALIVE), (point![0, 0], ALIVE)]);
+
+        // Multi-state leaves
+        let pattern = parse::<i32>("[M2]\n#R B3/S23/C4\n1 0 2 3 0\n2 0 0 0 1\n").unwrap();
+        assert_eq!(points(&pattern), [(point![1, 0], 2), (point![0, 1], 3)]);
+
+        // Shared nodes are built once per reference, as the tree built from cells
+        let pattern = parse::<i32>("[M2]\n.*$..*$***$\n4 1 0 1 1\n5 2 2 0 2\n").unwrap();
+        let cells = points(&pattern);
+        assert_eq!(cells.len(), 45);
+        assert_eq!(pattern.cells, cells.into_iter().collect());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse::<i32>("[M2]\n.*$..*a\n"), Err(ParseError::new(2, 7, "unexpected character in pattern")));
+        assert_eq!(parse::<i32>("[M2]\n.*.......*\n"), Err(ParseError::new(2, 9, "leaves must be 8x8 bitmaps")));
+        assert_eq!(parse::<i32>("[M2]\n*$\n4 1 0 0\n"), Err(ParseError::new(3, 1, "nodes must be formed as <level> <nw> <ne> <sw> <se>")));
+        assert_eq!(parse::<i32>("[M2]\n*$\n4 1 0 2 0\n"), Err(ParseError::new(3, 7, "node children must reference previous nodes")));
+        assert_eq!(parse::<i32>("[M2]\n*$\n5 1 0 0 0\n"), Err(ParseError::new(3, 3, "node children must be one level below it")));
+        assert_eq!(parse::<i32>("[M2]\n1 0 2 0 0\n"), Err(ParseError::new(2, 5, "cell state is not supported by rule")));
+        assert_eq!(parse::<i32>("[M2]\n*$\n33 0 0 0 0\n"), Err(ParseError::new(3, 1, "pattern exceeds coordinate limits")));
+
+        // Each level quadruples the population, up to 2^60 cells
+        let mut bomb = String::from("[M2]\n********$********$********$********$********$********$********$********$\n");
+
+        for level in 4..=30 {
+            let child = level - 3;
+            bomb += &format!("{level} {child} {child} {child} {child}\n");
+        }
+
+        assert_eq!(parse::<i64>(&bomb), Err(ParseError::new(29, 1, "pattern has too many living cells")));
+    }
+
+    #[test]
+    fn test_write() {
+        let pattern = parse::<i32>(GLIDER).unwrap();
+        assert_eq!(write(&pattern), "[M2] (game-of-life)\n#R B3/S23\n.*$..*$***$\n4 0 0 0 1\n");
+
+        // Cells around origin, in every quarter
+        let mut pattern = Pattern::<i32> { name: Some(String::from("Corners")), ..Pattern::default() };
+
+        for pt in [point![-1, -1], point![0, -1], point![-1, 0], point![0, 0]] {
+            pattern.cells.insert(pt, ALIVE);
+        }
+
+        assert_eq!(
+            write(&pattern),
+            "[M2] (game-of-life)\n#R B3/S23\n#N Corners\n$$$$$$$.......*$\n$$$$$$$*$\n.......*$\n*$\n4 1 2 3 4\n"
+        );
+    }
+
+    #[test]
+    fn test_write_deduplicates() {
+        // 256 blocks, laid on a grid: only one node per level is needed
+        let mut pattern = Pattern::<i64>::default();
+
+        for bx in 0..16 {
+            for by in 0..16 {
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    pattern.cells.insert(point![bx * 8 + dx, by * 8 + dy], ALIVE);
+                }
+            }
+        }
+
+        let output = write(&pattern);
+
+        assert_eq!(output, "[M2] (game-of-life)\n#R B3/S23\n**$**$\n4 1 1 1 1\n5 2 2 2 2\n6 3 3 3 3\n7 4 4 4 4\n8 0 0 0 5\n");
+        assert_eq!(parse::<i64>(&output).unwrap().cells, pattern.cells);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut pattern = Pattern::<i64> {
+            comments: vec![String::from("Far cells")],
+            rule: "B3/S23/C5".parse().unwrap(),
+            topology: Topology::Torus(na::Vector2::new(40, 30)),
+            ..Pattern::default()
+        };
+
+        pattern.cells.insert(point![i64::MIN, i64::MAX], 4);
+        pattern.cells.insert(point![-3, 7], 2);
+        pattern.cells.insert(point![1 << 40, -(1 << 20)], ALIVE);
+
+        let parsed = parse::<i64>(&write(&pattern)).unwrap();
+
+        assert_eq!(parsed, pattern);
+    }
+}
@@ -0,0 +1,157 @@
+use na::point;
+use crate::coordinate::Coordinate;
+use crate::pattern::{alive_cells, ParseError, Pattern};
+use crate::rule::ALIVE;
+
+/// Greatest number of characters of written grids, as plaintext stores dead cells too
+pub const MAX_GRID_SIZE: u64 = 1 << 26;
+
+/// Parses a plaintext pattern (.cells): a grid of '.' and 'O' with '!' comment lines.
+/// First row and column are placed at origin.
+pub fn parse<C: Coordinate>(input: &str) -> Result<Pattern<C>, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+    let mut y = Some(C::ZERO);
+
+    for (idx, line) in input.lines().enumerate() {
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(String::from(name.trim()));
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = Some(String::from(author.trim()));
+            } else {
+                pattern.comments.push(String::from(comment.trim()));
+            }
+
+            continue;
+        }
+
+        let mut x = Some(C::ZERO);
+
+        for (col, c) in line.chars().enumerate() {
+            let error = |message| ParseError::new(idx + 1, col + 1, message);
+
+            match c {
+                '.' => (),
+                'O' | '*' => match (x, y) {
+                    (Some(x), Some(y)) => cells.push((point![x, y], ALIVE)),
+                    _ => return Err(error("pattern exceeds coordinate limits")),
+                },
+                c if c.is_whitespace() => (),
+                _ => return Err(error("unexpected character in pattern")),
+            }
+
+            x = x.and_then(|x| x.checked_add(C::from_i32(1)));
+        }
+
+        y = y.and_then(|y| y.checked_add(C::from_i32(1)));
+    }
+
+    pattern.cells = cells.into_iter().collect();
+
+    Ok(pattern)
+}
+
+/// Writes pattern as plaintext.
+/// Only alive cells are written, first row and column holding a cell being moved to origin.
+/// Fails if the written grid would exceed MAX_GRID_SIZE characters.
+pub fn write<C: Coordinate>(pattern: &Pattern<C>) -> Result<String, &'static str> {
+    let cells = alive_cells(pattern);
+    let first_x = cells.iter().map(|pt| pt.x).min();
+
+    if let (Some(first), Some(last), Some(first_x), Some(last_x)) = (cells.first(), cells.last(), first_x, cells.iter().map(|pt| pt.x).max()) {
+        // Each row also holds its line break
+        let size = last_x.abs_diff(first_x).checked_add(2)
+            .zip(last.y.abs_diff(first.y).checked_add(1))
+            .and_then(|(width, height)| width.checked_mul(height))
+            .filter(|&size| size <= MAX_GRID_SIZE);
+
+        if size.is_none() {
+            return Err("pattern is too large to be written as plaintext");
+        }
+    }
+
+    let mut output = String::new();
+
+    if let Some(name) = &pattern.name {
+        output += &format!("!Name: {name}\n");
+    }
+
+    if let Some(author) = &pattern.author {
+        output += &format!("!Author: {author}\n");
+    }
+
+    for comment in &pattern.comments {
+        output += &format!("!{comment}\n");
+    }
+
+    let mut row = cells.first().map(|pt| pt.y);
+    let mut previous: Option<C> = None;
+
+    for pt in &cells {
+        if Some(pt.y) != row {
+            output += &"\n".repeat(pt.y.abs_diff(row.unwrap()) as usize);
+            row = Some(pt.y);
+            previous = None;
+        }
+
+        let gap = match previous {
+            Some(x) => pt.x.abs_diff(x) - 1,
+            None => pt.x.abs_diff(first_x.unwrap()),
+        };
+
+        output += &".".repeat(gap as usize);
+        output.push('O');
+        previous = Some(pt.x);
+    }
+
+    if !cells.is_empty() {
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use na::Point2;
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pattern = parse::<i32>("!Name: Glider\n!Author: Richard K. Guy\n!The smallest spaceship\n.O\n..O\nOOO\n").unwrap();
+
+        let mut cells = pattern.cells.iter().map(|(&pt, _)| pt).collect::<Vec<Point2<i32>>>();
+        cells.sort_by_key(|pt| (pt.y, pt.x));
+
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.comments, ["The smallest spaceship"]);
+        assert_eq!(cells, [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]]);
+
+        assert_eq!(parse::<i32>("!comment\n..O\n.x"), Err(ParseError::new(3, 2, "unexpected character in pattern")));
+    }
+
+    #[test]
+    fn test_write() {
+        let input = "!Name: Glider\n!The smallest spaceship\n.O\n..O\nOOO\n";
+        let pattern = parse::<i32>(input).unwrap();
+
+        assert_eq!(write(&pattern), Ok(String::from(input)));
+
+        // Moved to origin, keeping empty rows
+        let mut pattern = Pattern::<i64>::default();
+        pattern.cells.insert(point![-5, 10], ALIVE);
+        pattern.cells.insert(point![-3, 13], ALIVE);
+        pattern.cells.insert(point![-4, 12], 2);
+
+        assert_eq!(write(&pattern), Ok(String::from("O\n\n\n..O\n")));
+
+        // Too large grids are refused
+        pattern.cells.insert(point![i64::MAX, 0], ALIVE);
+        pattern.cells.insert(point![i64::MIN, 0], ALIVE);
+
+        assert_eq!(write(&pattern), Err("pattern is too large to be written as plaintext"));
+    }
+}
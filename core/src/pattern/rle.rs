@@ -0,0 +1,399 @@
+use na::{point, Point2};
+use crate::coordinate::Coordinate;
+use crate::pattern::{column_of, ParseError, Pattern, MAX_CELLS};
+use crate::rule::{ALIVE, DEAD};
+use crate::topology::Topology;
+
+/// Maximum length of written lines
+const LINE_LENGTH: usize = 70;
+
+/// Parses a run length encoded pattern, including its header and '#' lines
+pub fn parse<C: Coordinate>(input: &str) -> Result<Pattern<C>, ParseError> {
+    let mut pattern = Pattern::default();
+
+    let mut origin = point![C::ZERO, C::ZERO];
+    let mut body: Option<Body<C>> = None;
+    let mut cells = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let number = idx + 1;
+
+        match &mut body {
+            Some(body) => {
+                if body.parse_line(line, number, &pattern, &mut cells)? {
+                    break;
+                }
+            },
+            None if line.trim().is_empty() => (),
+            None if line.starts_with('#') => parse_comment(line, number, &mut pattern, &mut origin)?,
+            None => {
+                parse_header(line, number, &mut pattern)?;
+                body = Some(Body::new(origin));
+            },
+        }
+    }
+
+    pattern.cells = cells.into_iter().collect();
+
+    Ok(pattern)
+}
+
+/// Writes pattern in run length encoded format
+pub fn write<C: Coordinate>(pattern: &Pattern<C>) -> String {
+    let mut output = String::new();
+
+    if let Some(name) = &pattern.name {
+        output += &format!("#N {name}\n");
+    }
+
+    if let Some(author) = &pattern.author {
+        output += &format!("#O {author}\n");
+    }
+
+    for comment in &pattern.comments {
+        output += &format!("#C {comment}\n");
+    }
+
+    let (first, last) = match pattern.cells.bounding_box() {
+        Some(bbox) => (*bbox.start(), *bbox.end()),
+        None => (point![C::ZERO, C::ZERO], point![C::ZERO, C::ZERO]),
+    };
+
+    if first != point![C::ZERO, C::ZERO] {
+        output += &format!("#R {} {}\n", first.x, first.y);
+    }
+
+    let (width, height) = match pattern.cells.population() {
+        0 => (0, 0),
+        _ => (last.x.abs_diff(first.x) + 1, last.y.abs_diff(first.y) + 1),
+    };
+
+    output += &format!("x = {width}, y = {height}, rule = {}", pattern.rule);
+
+    if pattern.topology != Topology::Plane {
+        output += &format!(":{}", pattern.topology);
+    }
+
+    output.push('\n');
+
+    // Build runs, row by row
+    let multi_state = pattern.rule.states() > 2;
+    let mut cells = pattern.cells.iter().collect::<Vec<_>>();
+    cells.sort_by_key(|(pt, _)| (pt.y, pt.x));
+
+    let mut runs: Vec<(u64, String)> = Vec::new();
+    let mut push = |count: u64, tag: String| match runs.last_mut() {
+        Some((last_count, last_tag)) if *last_tag == tag => *last_count += count,
+        _ => runs.push((count, tag)),
+    };
+
+    let mut row = first.y;
+    let mut previous: Option<C> = None;
+
+    for (pt, &state) in cells {
+        if pt.y != row {
+            push(pt.y.abs_diff(row), String::from("$"));
+            row = pt.y;
+            previous = None;
+        }
+
+        let gap = match previous {
+            Some(x) => pt.x.abs_diff(x) - 1,
+            None => pt.x.abs_diff(first.x),
+        };
+
+        if gap > 0 {
+            push(gap, state_tag(DEAD, multi_state));
+        }
+
+        push(1, state_tag(state, multi_state));
+        previous = Some(pt.x);
+    }
+
+    // Wrap lines
+    let mut line = String::new();
+
+    for run in runs.iter().map(|(count, tag)| if *count == 1 { tag.clone() } else { format!("{count}{tag}") }).chain([String::from("!")]) {
+        if line.len() + run.len() > LINE_LENGTH {
+            output += &line;
+            output.push('\n');
+            line.clear();
+        }
+
+        line += &run;
+    }
+
+    output += &line;
+    output.push('\n');
+
+    output
+}
+
+// Utils
+/// State of the pattern body parser.
+/// Position is None once beyond coordinate limits, which is an error only if a cell is placed there.
+struct Body<C: Coordinate> {
+    start: C,
+    x: Option<C>,
+    y: Option<C>,
+    count: Option<i32>,
+    prefix: Option<u8>,
+}
+
+impl<C: Coordinate> Body<C> {
+    fn new(origin: Point2<C>) -> Body<C> {
+        Body {
+            start: origin.x,
+            x: Some(origin.x),
+            y: Some(origin.y),
+            count: None,
+            prefix: None,
+        }
+    }
+
+    /// Parses a line of the body, returning true if the pattern ended
+    fn parse_line(&mut self, line: &str, number: usize, pattern: &Pattern<C>, cells: &mut Vec<(Point2<C>, u8)>) -> Result<bool, ParseError> {
+        for (idx, c) in line.chars().enumerate() {
+            let error = |message| ParseError::new(number, idx + 1, message);
+
+            if self.prefix.is_some() && !c.is_ascii_uppercase() {
+                return Err(error("multi-state prefix must be followed by a state letter (A-X)"));
+            }
+
+            match c {
+                '0'..='9' => {
+                    self.count = self.count.unwrap_or(0).checked_mul(10)
+                        .and_then(|count| count.checked_add(c as i32 - '0' as i32));
+
+                    if self.count.is_none() {
+                        return Err(error("run count is too large"));
+                    }
+                },
+                'p'..='y' => self.prefix = Some(c as u8 - b'p' + 1),
+                'b' | '.' | 'o' | 'A'..='X' => {
+                    let state = match c {
+                        'b' | '.' => DEAD as u32,
+                        'o' => ALIVE as u32,
+                        _ => self.prefix.take().unwrap_or(0) as u32 * 24 + (c as u32 - 'A' as u32) + 1,
+                    };
+
+                    if state >= pattern.rule.states() as u32 {
+                        return Err(error("cell state is not supported by rule"));
+                    }
+
+                    let count = self.count.take().unwrap_or(1);
+
+                    if state == DEAD as u32 {
+                        self.x = self.x.and_then(|x| x.checked_add(C::from_i32(count)));
+                    } else {
+                        if cells.len() + count as usize > MAX_CELLS {
+                            return Err(error("pattern has too many living cells"));
+                        }
+
+                        for _ in 0..count {
+                            let (Some(x), Some(y)) = (self.x, self.y) else {
+                                return Err(error("pattern exceeds coordinate limits"));
+                            };
+
+                            cells.push((point![x, y], state as u8));
+                            self.x = x.checked_add(C::from_i32(1));
+                        }
+                    }
+                },
+                '$' => {
+                    let count = self.count.take().unwrap_or(1);
+
+                    self.x = Some(self.start);
+                    self.y = self.y.and_then(|y| y.checked_add(C::from_i32(count)));
+                },
+                '!' => return Ok(true),
+                c if c.is_whitespace() => (),
+                _ => return Err(error("unexpected character in pattern")),
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Parses '#' lines (name, author, comments, position and rule)
+fn parse_comment<C: Coordinate>(line: &str, number: usize, pattern: &mut Pattern<C>, origin: &mut Point2<C>) -> Result<(), ParseError> {
+    let mut chars = line[1..].chars();
+    let kind = chars.next().unwrap_or(' ');
+    let content = chars.as_str().trim();
+
+    match kind {
+        'N' => pattern.name = Some(String::from(content)),
+        'O' => pattern.author = Some(String::from(content)),
+        'C' | 'c' => pattern.comments.push(String::from(content)),
+        'P' | 'R' => {
+            let coords = content.split_whitespace()
+                .map(|coord| coord.parse::<C>().ok())
+                .collect::<Vec<_>>();
+
+            match coords[..] {
+                [Some(x), Some(y)] => *origin = point![x, y],
+                _ => return Err(ParseError::new(number, column_of(line, content), "position must be 2 integers")),
+            }
+        },
+        'r' => {
+            pattern.rule = content.parse()
+                .map_err(|message| ParseError::new(number, column_of(line, content), message))?;
+        },
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Parses header line (x = <width>, y = <height>, rule = <rule>)
+fn parse_header<C: Coordinate>(line: &str, number: usize, pattern: &mut Pattern<C>) -> Result<(), ParseError> {
+    let mut rest = line;
+
+    while !rest.trim().is_empty() {
+        let (key, value) = rest.split_once('=')
+            .ok_or(ParseError::new(number, column_of(line, rest), "header entries must be formed as <key> = <value>"))?;
+
+        match key.trim() {
+            "rule" => {
+                // Rule may contain ',', so it takes the rest of the line
+                let (rule, topology) = value.split_once(':').unwrap_or((value, ""));
+
+                pattern.rule = rule.trim().parse()
+                    .map_err(|message| ParseError::new(number, column_of(line, rule), message))?;
+                pattern.topology = topology.trim().parse()
+                    .map_err(|message| ParseError::new(number, column_of(line, topology), message))?;
+
+                return Ok(());
+            },
+            "x" | "y" => {
+                let (size, next) = value.split_once(',').unwrap_or((value, ""));
+
+                if size.trim().parse::<u64>().is_err() {
+                    return Err(ParseError::new(number, column_of(line, size), "pattern size must be a positive integer"));
+                }
+
+                rest = next;
+            },
+            _ => return Err(ParseError::new(number, column_of(line, key), "unknown header entry")),
+        }
+    }
+
+    Ok(())
+}
+
+/// Tag of given state
+fn state_tag(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (DEAD, false) => String::from("b"),
+        (_, false) => String::from("o"),
+        (DEAD, true) => String::from("."),
+        (1..=24, true) => String::from((b'A' + state - 1) as char),
+        (_, true) => {
+            let state = state - 25;
+            format!("{}{}", (b'p' + state / 24) as char, (b'A' + state % 24) as char)
+        },
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use crate::rule::Rule;
+    use super::*;
+
+    fn points(pattern: &Pattern) -> Vec<(Point2<i32>, u8)> {
+        let mut cells = pattern.cells.iter().map(|(&pt, &state)| (pt, state)).collect::<Vec<_>>();
+        cells.sort_by_key(|(pt, _)| (pt.y, pt.x));
+        cells
+    }
+
+    #[test]
+    fn test_parse() {
+        let pattern = parse::<i32>("#N Glider\n#O Richard K. Guy\n#C The smallest spaceship\n#C Found in 1969\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.comments, ["The smallest spaceship", "Found in 1969"]);
+        assert_eq!(pattern.rule, Rule::conway());
+        assert_eq!(points(&pattern), [
+            (point![1, 0], 1), (point![2, 1], 1), (point![0, 2], 1), (point![1, 2], 1), (point![2, 2], 1)
+        ]);
+    }
+
+    #[test]
+    fn test_parse_multiline() {
+        let pattern = parse::<i32>("#P -2 5\nx = 4, y = 3\n2o\n2b\n2o2$\n4o!\nignored").unwrap();
+
+        assert_eq!(points(&pattern), [
+            (point![-2, 5], 1), (point![-1, 5], 1), (point![2, 5], 1), (point![3, 5], 1),
+            (point![-2, 7], 1), (point![-1, 7], 1), (point![0, 7], 1), (point![1, 7], 1),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_multi_state() {
+        let pattern = parse::<i32>("x = 3, y = 2, rule = R2,C30,M0,S3..3,B4..4,NM:T20,10\nA.B$pA2C!").unwrap();
+
+        assert_eq!(pattern.rule.states(), 30);
+        assert_eq!(pattern.topology, Topology::Torus(na::Vector2::new(20, 10)));
+        assert_eq!(points(&pattern), [
+            (point![0, 0], 1), (point![2, 0], 2), (point![0, 1], 25), (point![1, 1], 3), (point![2, 1], 3)
+        ]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse::<i32>("x = 3, y = 3\nbo$2bo$3z!"),
+            Err(ParseError::new(2, 9, "unexpected character in pattern"))
+        );
+        assert_eq!(
+            parse::<i32>("#C comment\nx = 3, y = a, rule = B3/S23"),
+            Err(ParseError::new(2, 12, "pattern size must be a positive integer"))
+        );
+        assert_eq!(
+            parse::<i32>("x = 3, y = 3, rule = B3/S23/1\nbo!"),
+            Err(ParseError::new(1, 22, "number of states must be an integer between 2 and 255"))
+        );
+        assert_eq!(
+            parse::<i32>("x = 3, y = 3\n\n  3B!"),
+            Err(ParseError::new(3, 4, "cell state is not supported by rule"))
+        );
+        assert_eq!(
+            parse::<i32>("x = 3, y = 3, rule = B3/S23/C30\npo!"),
+            Err(ParseError::new(2, 2, "multi-state prefix must be followed by a state letter (A-X)"))
+        );
+        assert_eq!(
+            parse::<i32>("x = 3, y = 3\n2147483647b2o!"),
+            Err(ParseError::new(2, 13, "pattern exceeds coordinate limits"))
+        );
+        assert_eq!(
+            parse::<i32>("x = 1, y = 1\n2147483647o!"),
+            Err(ParseError::new(2, 11, "pattern has too many living cells"))
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let pattern = parse::<i32>("#N Glider\n#C The smallest spaceship\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+
+        assert_eq!(write(&pattern), "#N Glider\n#C The smallest spaceship\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+
+        // Position, long runs and wrapped lines
+        let input = format!("#R -3 2\nx = 200, y = 5, rule = B3/S23/C4:T300,300\n{}\n", "A2.78B2$5C.A190.B!");
+        let pattern = parse::<i32>(&input).unwrap();
+
+        assert_eq!(write(&pattern), "#R -3 2\nx = 198, y = 3, rule = B3/S23/C4:T300,300\nA2.78B2$5C.A190.B!\n");
+        assert_eq!(parse::<i32>(&write(&pattern)), Ok(pattern));
+
+        let mut long = String::from("x = 80, y = 1, rule = B3/S23\n");
+        long += &"ob".repeat(40);
+        long += "!";
+
+        let written = write(&parse::<i32>(&long).unwrap());
+
+        assert!(written.lines().all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(parse::<i32>(&written), parse::<i32>(&long));
+    }
+}
@@ -0,0 +1,1107 @@
+use std::{array, mem};
+use std::cmp::min;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use na::{point, Point2, Vector2};
+use crate::coordinate::Coordinate;
+use py::{Holds, Overlaps};
+use crate::quadtree::arena::{Arena, NodeId};
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::global_node::GlobalNode;
+use crate::quadtree::iter::Iter;
+use crate::quadtree::node::Node;
+use crate::quadtree::operation::{Combinator, SetOperation};
+use crate::quadtree::query::Query;
+use crate::quadtree::quarter::global_quarter;
+use crate::quadtree::square_node::SquareNode;
+use crate::quadtree::tree::Tree;
+use crate::quadtree::tree::same_structure;
+use crate::symmetry::Symmetry;
+use crate::utils::cmp_z_order;
+
+pub mod arena;
+pub mod binary_square;
+pub mod global_node;
+pub mod iter;
+pub mod node;
+pub mod operation;
+pub mod quarter;
+pub mod query;
+pub mod square_node;
+pub mod tree;
+
+/// Quadtree wrapper, owning its nodes and mapping points to values
+#[derive(Clone, Debug)]
+pub struct Quadtree<N: Node<T, C>, T, C: Coordinate = i32> {
+    root: N,
+    nodes: Arena<T, C>,
+}
+
+impl<N: Node<T, C>, T, C: Coordinate> Quadtree<N, T, C> {
+    /// Builds quadtree from its root, and the arena holding its nodes
+    #[inline]
+    pub fn from_parts(root: N, nodes: Arena<T, C>) -> Quadtree<N, T, C> {
+        Quadtree { root, nodes }
+    }
+
+    #[inline]
+    pub fn root(&self) -> &N {
+        &self.root
+    }
+
+    /// Returns arena holding nodes of the tree
+    #[inline]
+    pub fn nodes(&self) -> &Arena<T, C> {
+        &self.nodes
+    }
+
+    #[inline]
+    pub fn has(&self, point: &Point2<C>) -> bool {
+        self.root.has(&self.nodes, point)
+    }
+
+    /// Returns value of given point, if present
+    #[inline]
+    pub fn get(&self, point: &Point2<C>) -> Option<&T> {
+        self.root.get(&self.nodes, point)
+    }
+
+    /// Returns mutable reference to value of given point, if present
+    #[inline]
+    pub fn get_mut(&mut self, point: &Point2<C>) -> Option<&mut T> where T: Clone {
+        self.root.get_mut(&mut self.nodes, point)
+    }
+
+    /// Number of points in tree
+    #[inline]
+    pub fn population(&self) -> usize {
+        self.root.population()
+    }
+
+    /// Counts points inside given area
+    #[inline]
+    pub fn count_in<B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(&self, area: &B) -> usize {
+        self.root.count_in(&self.nodes, area)
+    }
+
+    /// Returns smallest area holding all points, if any
+    pub fn bounding_box(&self) -> Option<RangeInclusive<Point2<C>>> {
+        let mut bounds = None;
+        self.root.extend_bounds(&self.nodes, &mut bounds);
+
+        bounds.map(|(first, last)| first..=last)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, C> {
+        Iter::new(&self.root, &self.nodes)
+    }
+
+    pub fn query<B: Clone + Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(&self, bbox: &B) -> Query<'_, B, T, C> {
+        Query::new(bbox, &self.root, &self.nodes)
+    }
+
+    /// Inserts value at given point, returning its previous value
+    #[inline]
+    pub fn insert(&mut self, point: Point2<C>, value: T) -> Option<T> where T: Clone {
+        self.root.insert(&mut self.nodes, point, value)
+    }
+
+    /// Removes given point, returning its value
+    #[inline]
+    pub fn remove(&mut self, point: &Point2<C>) -> Option<T> where T: Clone {
+        self.root.remove(&mut self.nodes, point)
+    }
+
+    /// Removes all points inside given area, returning their count.
+    /// Subtrees fully inside area are pruned at once.
+    #[inline]
+    pub fn remove_area<B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(&mut self, area: &B) -> usize where T: Clone {
+        self.root.remove_area(&mut self.nodes, area, None).0
+    }
+}
+
+pub type GlobalQuadtree<T, C = i32> = Quadtree<GlobalNode<T, C>, T, C>;
+
+impl<T, C: Coordinate> Quadtree<GlobalNode<T, C>, T, C> {
+    pub fn new() -> GlobalQuadtree<T, C> {
+        Quadtree::from_parts(GlobalNode::new(), Arena::new())
+    }
+}
+
+impl<T: Clone, C: Coordinate> Quadtree<GlobalNode<T, C>, T, C> {
+    /// Compacts node storage, releasing memory of removed nodes
+    #[inline]
+    pub fn shrink(&mut self) {
+        self.nodes.compact(self.root.children.iter_mut());
+    }
+
+    /// Splits off points inside given area, returning them as a new tree.
+    /// Subtrees fully inside area are moved at once.
+    pub fn extract<B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(&mut self, area: &B) -> GlobalQuadtree<T, C> {
+        let mut nodes = Arena::new();
+        let (population, children) = self.root.remove_area(&mut self.nodes, area, Some(&mut nodes));
+
+        Quadtree::from_parts(GlobalNode { population, children }, nodes)
+    }
+
+    /// Applies set operation between both trees.
+    /// Values of self are kept for points held by both trees.
+    pub fn combine(&self, other: &GlobalQuadtree<T, C>, operation: SetOperation) -> GlobalQuadtree<T, C> {
+        let mut combinator = Combinator::new(operation, &self.nodes, &other.nodes);
+        let children = array::from_fn(|idx| combinator.combine(&self.root.children[idx], &other.root.children[idx]));
+
+        let nodes = combinator.into_nodes();
+        let population = children.iter().map(|child| child.population(&nodes)).sum();
+
+        Quadtree::from_parts(GlobalNode { population, children }, nodes)
+    }
+
+    /// Returns points held by any of both trees
+    #[inline]
+    pub fn union(&self, other: &GlobalQuadtree<T, C>) -> GlobalQuadtree<T, C> {
+        self.combine(other, SetOperation::Union)
+    }
+
+    /// Returns points held by both trees
+    #[inline]
+    pub fn intersection(&self, other: &GlobalQuadtree<T, C>) -> GlobalQuadtree<T, C> {
+        self.combine(other, SetOperation::Intersection)
+    }
+
+    /// Returns points held by self but not by other
+    #[inline]
+    pub fn difference(&self, other: &GlobalQuadtree<T, C>) -> GlobalQuadtree<T, C> {
+        self.combine(other, SetOperation::Difference)
+    }
+
+    /// Returns points held by only one of both trees
+    #[inline]
+    pub fn symmetric_difference(&self, other: &GlobalQuadtree<T, C>) -> GlobalQuadtree<T, C> {
+        self.combine(other, SetOperation::SymmetricDifference)
+    }
+
+    /// Returns tree with all points moved by given offset.
+    /// Points moved beyond coordinate limits are dropped.
+    /// Subtrees aligned on the offset are moved at once, only other points are rebuilt.
+    pub fn translate(&self, offset: &Vector2<C>) -> GlobalQuadtree<T, C> {
+        let mut subtrees = Vec::new();
+        let mut points = Vec::new();
+
+        for child in &self.root.children {
+            split_translated(&self.nodes, child, offset, &mut subtrees, &mut points);
+        }
+
+        let mut tree = points.into_iter().collect::<GlobalQuadtree<T, C>>();
+
+        for id in subtrees {
+            let subtree = copy_translated(&self.nodes, id, offset, &mut tree.nodes);
+            let area = tree.nodes[subtree].area;
+
+            tree.root.population += tree.nodes[subtree].population;
+            graft(&mut tree.nodes, tree.root.child_holding_mut(&area.anchor), Tree::Node(subtree), &area);
+        }
+
+        tree
+    }
+
+    /// Returns tree with all points mapped by given symmetry.
+    /// As symmetries map squares to squares, the structure is kept and only node areas and children order change.
+    pub fn transform(&self, symmetry: Symmetry) -> GlobalQuadtree<T, C> {
+        let mut tree = self.clone();
+
+        for node in tree.nodes.iter_mut() {
+            let (first, last) = (symmetry.apply(&node.area.anchor), symmetry.apply(&node.area.last_point()));
+            node.area.anchor = point![min(first.x, last.x), min(first.y, last.y)];
+
+            transform_children(&mut node.children, symmetry);
+        }
+
+        transform_children(&mut tree.root.children, symmetry);
+        tree
+    }
+}
+
+// Utils
+impl<T, C: Coordinate> Default for Quadtree<GlobalNode<T, C>, T, C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Node<T, C>, T: PartialEq, C: Coordinate> PartialEq for Quadtree<N, T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.root.population() == other.root.population()
+            && self.root.children().zip(other.root.children())
+                .all(|(lhs, rhs)| same_structure(lhs, &self.nodes, rhs, &other.nodes))
+    }
+}
+
+impl<N: Node<T, C>, T: Eq, C: Coordinate> Eq for Quadtree<N, T, C> {}
+
+impl<T: Clone, C: Coordinate> FromIterator<(Point2<C>, T)> for Quadtree<GlobalNode<T, C>, T, C> {
+    /// Builds tree from sorted points, without inserting them one by one.
+    /// Last value is kept for duplicated points.
+    fn from_iter<I: IntoIterator<Item = (Point2<C>, T)>>(iter: I) -> Self {
+        let mut points = iter.into_iter()
+            .map(|(pt, value)| (pt, Some(value)))
+            .collect::<Vec<_>>();
+
+        points.reverse();
+        points.sort_by(|(a, _), (b, _)| cmp_z_order(a, b));
+        points.dedup_by(|(a, _), (b, _)| a == b);
+
+        let mut nodes = Arena::new();
+        let mut root = GlobalNode::new();
+        root.population = points.len();
+
+        let mut rest = points.as_mut_slice();
+
+        for (idx, child) in root.children.iter_mut().enumerate() {
+            let (quarter, others) = rest.split_at_mut(rest.partition_point(|(pt, _)| global_quarter(pt) as usize <= idx));
+
+            *child = build_tree(&mut nodes, quarter);
+            rest = others;
+        }
+
+        Quadtree::from_parts(root, nodes)
+    }
+}
+
+impl<'a, N: Node<T, C>, T, C: Coordinate> IntoIterator for &'a Quadtree<N, T, C> {
+    type Item = (&'a Point2<C>, &'a T);
+    type IntoIter = Iter<'a, T, C>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Builds tree holding given points, sorted in z-order and all in the same global quarter
+fn build_tree<T: Clone, C: Coordinate>(nodes: &mut Arena<T, C>, points: &mut [(Point2<C>, Option<T>)]) -> Tree<T, C> {
+    match points {
+        [] => Tree::Empty,
+        [(pt, value)] => Tree::Leaf(*pt, value.take().unwrap()),
+        [(first, _), .., (last, _)] => {
+            // Points between first and last in z-order are all inside their common square
+            let area = BinarySquare::common(&BinarySquare::wrapping(*first), &BinarySquare::wrapping(*last)).unwrap();
+
+            let mut node = SquareNode::new(area);
+            node.population = points.len();
+
+            let mut rest = points;
+
+            for (idx, child) in node.children.iter_mut().enumerate() {
+                let (quarter, others) = rest.split_at_mut(rest.partition_point(|(pt, _)| area.quarter(pt) as usize <= idx));
+
+                *child = build_tree(nodes, quarter);
+                rest = others;
+            }
+
+            Tree::Node(nodes.alloc(node))
+        },
+    }
+}
+
+/// Collects nodes which can be moved at once by offset (keeping them aligned and inside coordinate limits),
+/// and moved points found outside of them
+fn split_translated<T: Clone, C: Coordinate>(nodes: &Arena<T, C>, tree: &Tree<T, C>, offset: &Vector2<C>, subtrees: &mut Vec<NodeId>, points: &mut Vec<(Point2<C>, T)>) {
+    match tree {
+        Tree::Empty => (),
+        Tree::Leaf(pt, value) => {
+            if let (Some(x), Some(y)) = (pt.x.checked_add(offset.x), pt.y.checked_add(offset.y)) {
+                points.push((point![x, y], value.clone()));
+            }
+        },
+        &Tree::Node(id) => {
+            let area = nodes[id].area;
+            let (first, last) = (area.anchor, area.last_point());
+
+            let mask = C::from_bits(area.size - 1);
+            let aligned = offset.x & mask == C::ZERO && offset.y & mask == C::ZERO;
+            let inside = first.x.checked_add(offset.x).is_some() && first.y.checked_add(offset.y).is_some()
+                && last.x.checked_add(offset.x).is_some() && last.y.checked_add(offset.y).is_some();
+
+            if aligned && inside {
+                subtrees.push(id);
+            } else {
+                for child in &nodes[id].children {
+                    split_translated(nodes, child, offset, subtrees, points);
+                }
+            }
+        },
+    }
+}
+
+/// Copies node and its subtree into target arena, moved by offset
+fn copy_translated<T: Clone, C: Coordinate>(nodes: &Arena<T, C>, id: NodeId, offset: &Vector2<C>, target: &mut Arena<T, C>) -> NodeId {
+    let node = &nodes[id];
+    let mut copy = SquareNode::new(BinarySquare { anchor: node.area.anchor + offset, size: node.area.size });
+    copy.population = node.population;
+
+    for (child, copied) in node.children.iter().zip(copy.children.iter_mut()) {
+        *copied = match child {
+            Tree::Empty => Tree::Empty,
+            Tree::Leaf(pt, value) => Tree::Leaf(pt + offset, value.clone()),
+            &Tree::Node(child) => Tree::Node(copy_translated(nodes, child, offset, target)),
+        };
+    }
+
+    target.alloc(copy)
+}
+
+/// Inserts tree covering given area at pos, area being disjoint from all trees already there
+fn graft<T: Clone, C: Coordinate>(nodes: &mut Arena<T, C>, pos: &mut Tree<T, C>, tree: Tree<T, C>, area: &BinarySquare<C>) {
+    let previous = match pos {
+        Tree::Empty => {
+            *pos = tree;
+            return;
+        },
+        Tree::Leaf(pt, _) => BinarySquare::wrapping(*pt),
+        &mut Tree::Node(id) => {
+            let node_area = nodes[id].area;
+
+            if node_area.holds(area) {
+                let population = tree.population(nodes);
+
+                let mut node = mem::replace(&mut nodes[id], SquareNode::new(node_area));
+                graft(nodes, node.child_holding_mut(&area.anchor), tree, area);
+                node.population += population;
+
+                nodes[id] = node;
+                return;
+            }
+
+            node_area
+        },
+    };
+
+    // Both trees become children of their common square
+    let mut upper = SquareNode::new(BinarySquare::common(&previous, area).unwrap());
+    upper.population = pos.population(nodes) + tree.population(nodes);
+
+    *upper.child_holding_mut(&area.anchor) = tree;
+    *upper.child_holding_mut(&previous.anchor) = mem::replace(pos, Tree::Empty);
+
+    *pos = Tree::Node(nodes.alloc(upper));
+}
+
+/// Moves leaves and reorders children as mapped by given symmetry
+fn transform_children<T, C: Coordinate>(children: &mut [Tree<T, C>; 4], symmetry: Symmetry) {
+    let mut transformed = [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty];
+
+    for (idx, child) in children.iter_mut().enumerate() {
+        if let Tree::Leaf(pt, _) = child {
+            *pt = symmetry.apply(pt);
+        }
+
+        transformed[symmetry.apply_quarter(idx)] = mem::replace(child, Tree::Empty);
+    }
+
+    *children = transformed;
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+    use na::point;
+    use crate::quadtree::square_node::SquareNode;
+    use crate::quadtree::tree::Tree;
+    use super::*;
+
+    #[test]
+    fn test_has_point() {
+        // Initiate tree
+        let mut tree = Quadtree::default();
+        tree.insert(point![3, 1], 1);
+        tree.insert(point![3, 3], 1);
+        tree.insert(point![3, 5], 1);
+
+        // Inserted points
+        assert!(tree.has(&point![3, 1]));
+        assert!(tree.has(&point![3, 3]));
+        assert!(tree.has(&point![3, 5]));
+
+        // Others
+        assert!(!tree.has(&point![0, 0]));
+        assert!(!tree.has(&point![12, 42]));
+    }
+
+    #[test]
+    fn test_get_state() {
+        // Initiate tree
+        let mut tree = Quadtree::default();
+        tree.insert(point![3, 1], 1);
+        tree.insert(point![3, 3], 2);
+
+        assert_eq!(tree.get(&point![3, 1]), Some(&1));
+        assert_eq!(tree.get(&point![3, 3]), Some(&2));
+        assert_eq!(tree.get(&point![3, 5]), None);
+
+        // Replace state
+        tree.insert(point![3, 3], 3);
+
+        assert_eq!(tree.get(&point![3, 3]), Some(&3));
+        assert_eq!(tree.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_values() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        assert_eq!(tree.insert(point![3, 1], String::from("a")), None);
+        assert_eq!(tree.insert(point![3, 3], String::from("b")), None);
+        assert_eq!(tree.insert(point![-5, 2], String::from("c")), None);
+
+        // Replace value
+        assert_eq!(tree.insert(point![3, 3], String::from("d")), Some(String::from("b")));
+        assert_eq!(tree.population(), 3);
+
+        // Update in place
+        tree.get_mut(&point![3, 1]).unwrap().push('e');
+        tree.get_mut(&point![-5, 2]).unwrap().push('f');
+
+        assert_eq!(tree.get(&point![3, 1]).map(String::as_str), Some("ae"));
+        assert_eq!(tree.get(&point![-5, 2]).map(String::as_str), Some("cf"));
+        assert_eq!(tree.get_mut(&point![3, 2]), None);
+
+        // Remove
+        assert_eq!(tree.remove(&point![3, 3]), Some(String::from("d")));
+        assert_eq!(tree.remove(&point![3, 3]), None);
+        assert_eq!(tree.population(), 2);
+    }
+
+    #[test]
+    fn test_count_in() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for x in -8..8 {
+            for y in -8..8 {
+                if (x + y) % 3 == 0 {
+                    tree.insert(point![x, y], 1);
+                }
+            }
+        }
+
+        let expected = |tree: &GlobalQuadtree<u8>, area: &Range<Point2<i32>>| tree.iter().filter(|(pt, _)| area.holds(*pt)).count();
+
+        assert_eq!(tree.population(), tree.iter().count());
+        assert_eq!(tree.count_in(&(..)), tree.population());
+
+        for area in [point![0, 0]..point![8, 8], point![-5, -3]..point![2, 7], point![-8, 4]..point![-4, 5]] {
+            assert_eq!(tree.count_in(&area), expected(&tree, &area));
+        }
+
+        // Population follows updates
+        tree.insert(point![0, 0], 2);
+        tree.remove(&point![1, 2]);
+        tree.remove(&point![1, 2]);
+
+        assert_eq!(tree.population(), tree.iter().count());
+        assert_eq!(tree.count_in(&(point![0, 0]..point![8, 8])), expected(&tree, &(point![0, 0]..point![8, 8])));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+        assert_eq!(tree.bounding_box(), None);
+
+        tree.insert(point![3, 1], 1);
+        assert_eq!(tree.bounding_box(), Some(point![3, 1]..=point![3, 1]));
+
+        tree.insert(point![5, 2], 1);
+        tree.insert(point![4, 7], 1);
+        tree.insert(point![-6, -1], 1);
+        tree.insert(point![2, -9], 1);
+        assert_eq!(tree.bounding_box(), Some(point![-6, -9]..=point![5, 7]));
+
+        tree.remove(&point![-6, -1]);
+        tree.remove(&point![2, -9]);
+        assert_eq!(tree.bounding_box(), Some(point![3, 1]..=point![5, 7]));
+    }
+
+    #[test]
+    fn test_limits() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+        let points = [point![i32::MIN, i32::MIN], point![-1, -1], point![0, 0], point![i32::MAX, 1], point![i32::MAX, i32::MAX]];
+
+        for point in points {
+            tree.insert(point, 1);
+        }
+
+        assert!(points.iter().all(|point| tree.has(point)));
+        assert_eq!(tree.count_in(&(point![0, 0]..=point![i32::MAX, i32::MAX])), 3);
+        assert_eq!(tree.bounding_box(), Some(point![i32::MIN, i32::MIN]..=point![i32::MAX, i32::MAX]));
+
+        for point in points {
+            assert_eq!(tree.remove(&point), Some(1));
+        }
+
+        assert!(tree.nodes().is_empty());
+
+        // 64 bits coordinates
+        let mut tree = GlobalQuadtree::<u8, i64>::default();
+        tree.insert(point![1 << 40, 3], 1);
+        tree.insert(point![i64::MAX, 3], 2);
+
+        assert_eq!(tree.get(&point![1 << 40, 3]), Some(&1));
+        assert_eq!(tree.get(&point![i64::MAX, 3]), Some(&2));
+        assert_eq!(tree.bounding_box(), Some(point![1 << 40, 3]..=point![i64::MAX, 3]));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        // Initiate trees
+        let mut lhs = GlobalQuadtree::default();
+        let mut rhs = GlobalQuadtree::default();
+
+        for x in -20..20 {
+            for y in -20..20 {
+                if (x * y) % 3 == 0 {
+                    lhs.insert(point![x, y], 1);
+                }
+
+                if (x + 2 * y) % 5 == 0 && x > -10 {
+                    rhs.insert(point![x, y], 2);
+                }
+            }
+        }
+
+        rhs.insert(point![1000, -1000], 2);
+
+        let expected = |keep: fn(bool, bool) -> bool| {
+            let mut tree = GlobalQuadtree::default();
+
+            for (&pt, &value) in lhs.iter().chain(rhs.iter()) {
+                if keep(lhs.has(&pt), rhs.has(&pt)) && !tree.has(&pt) {
+                    tree.insert(pt, if lhs.has(&pt) { 1 } else { value });
+                }
+            }
+
+            tree
+        };
+
+        for (result, keep) in [
+            (lhs.union(&rhs), (|l, r| l || r) as fn(bool, bool) -> bool),
+            (lhs.intersection(&rhs), |l, r| l && r),
+            (lhs.difference(&rhs), |l, r| l && !r),
+            (lhs.symmetric_difference(&rhs), |l, r| l != r),
+        ] {
+            let expected = expected(keep);
+
+            assert_eq!(result, expected);
+            assert_eq!(result.population(), expected.iter().count());
+            assert_eq!(result.nodes().len(), expected.nodes().len());
+        }
+
+        // With empty trees
+        let empty = GlobalQuadtree::default();
+
+        assert_eq!(lhs.union(&empty), lhs);
+        assert_eq!(empty.difference(&lhs), empty);
+        assert_eq!(lhs.intersection(&empty), empty);
+        assert_eq!(lhs.symmetric_difference(&lhs), empty);
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let points = (-20..20).flat_map(|x| (-20..20).map(move |y| point![x * 7 % 13, y * x % 11]));
+
+        let mut expected = GlobalQuadtree::default();
+        points.clone().enumerate().for_each(|(idx, pt)| { expected.insert(pt, idx); });
+
+        let tree = points.enumerate().map(|(idx, pt)| (pt, idx)).collect::<GlobalQuadtree<_>>();
+
+        assert_eq!(tree, expected);
+        assert_eq!(tree.nodes().len(), expected.nodes().len());
+    }
+
+    #[test]
+    fn test_transform() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for x in -8..8 {
+            tree.insert(point![x, x * x - 10], 1);
+            tree.insert(point![3 * x, 5], 2);
+        }
+
+        tree.insert(point![i32::MIN, i32::MAX], 3);
+
+        for symmetry in [
+            Symmetry::Rotate90, Symmetry::Rotate180, Symmetry::Rotate270,
+            Symmetry::FlipHorizontal, Symmetry::FlipVertical, Symmetry::FlipDiagonal, Symmetry::FlipAntiDiagonal
+        ] {
+            let expected = tree.iter()
+                .map(|(pt, &value)| (symmetry.apply(pt), value))
+                .collect::<GlobalQuadtree<_>>();
+
+            assert_eq!(tree.transform(symmetry), expected);
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+        tree.insert(point![3, 1], 1);
+        tree.insert(point![-3, 5], 2);
+        tree.insert(point![i32::MAX - 1, 0], 3);
+
+        let moved = tree.translate(&Vector2::new(2, -3));
+
+        assert_eq!(moved.population(), 2);
+        assert_eq!(moved.get(&point![5, -2]), Some(&1));
+        assert_eq!(moved.get(&point![-1, 2]), Some(&2));
+        assert_eq!(moved.translate(&Vector2::new(-2, 3)).population(), 2);
+    }
+
+    #[test]
+    fn test_translate_subtrees() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for (x, y) in [(0, 0), (1, 1), (3, 2), (-5, 6), (-6, 7), (17, -9), (i32::MAX, 4)] {
+            tree.insert(point![x, y], 1);
+        }
+
+        let expected = |offset: Vector2<i32>| tree.iter()
+            .filter_map(|(pt, &value)| Some((point![pt.x.checked_add(offset.x)?, pt.y.checked_add(offset.y)?], value)))
+            .collect::<GlobalQuadtree<_>>();
+
+        // Aligned offsets move whole subtrees, keeping the tree structure
+        for offset in [Vector2::new(8, -16), Vector2::new(-4, 4), Vector2::new(1, 0), Vector2::new(-32, 64)] {
+            assert_eq!(tree.translate(&offset), expected(offset));
+        }
+
+        let small = tree.query(&(point![0, 0]..point![8, 8])).map(|(&pt, &value)| (pt, value)).collect::<GlobalQuadtree<_>>();
+        assert_eq!(small.translate(&Vector2::new(-8, -8)).nodes().len(), small.nodes().len());
+    }
+
+    #[test]
+    fn test_remove_area() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for x in -32..32 {
+            for y in -32..32 {
+                if (x + y) % 3 == 0 {
+                    tree.insert(point![x, y], 1);
+                }
+            }
+        }
+
+        let area = point![-20, -5]..=point![31, 17];
+        let mut expected = GlobalQuadtree::default();
+
+        for (&pt, &value) in tree.iter().filter(|(pt, _)| !area.holds(*pt)) {
+            expected.insert(pt, value);
+        }
+
+        let count = tree.count_in(&area);
+
+        assert_eq!(tree.remove_area(&area), count);
+        assert_eq!(tree, expected);
+        assert_eq!(tree.nodes().len(), expected.nodes().len());
+
+        assert_eq!(tree.remove_area(&(..)), expected.population());
+        assert!(tree.nodes().is_empty());
+    }
+
+    #[test]
+    fn test_extract() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for x in -32..32 {
+            for y in -32..32 {
+                if (x * y) % 5 == 1 {
+                    tree.insert(point![x, y], x);
+                }
+            }
+        }
+
+        let area = point![-7, -30]..point![25, 3];
+        let (mut inside, mut outside) = (GlobalQuadtree::default(), GlobalQuadtree::default());
+
+        for (&pt, &value) in tree.iter() {
+            if area.holds(&pt) {
+                inside.insert(pt, value);
+            } else {
+                outside.insert(pt, value);
+            }
+        }
+
+        let extracted = tree.extract(&area);
+
+        assert_eq!(extracted, inside);
+        assert_eq!(tree, outside);
+        assert_eq!(extracted.nodes().len(), inside.nodes().len());
+        assert_eq!(tree.nodes().len(), outside.nodes().len());
+    }
+
+    #[test]
+    fn test_remove_frees_nodes() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for x in 0..8 {
+            tree.insert(point![x, x], 1);
+        }
+
+        let slots = tree.nodes().slots();
+
+        for x in 0..8 {
+            tree.remove(&point![x, x]);
+        }
+
+        assert!(tree.nodes().is_empty());
+
+        // Removed nodes are reused
+        for x in 0..8 {
+            tree.insert(point![x, 7 - x], 1);
+        }
+
+        assert_eq!(tree.nodes().slots(), slots);
+    }
+
+    #[test]
+    fn test_shrink() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+
+        for x in -16..16 {
+            tree.insert(point![x, 2 * x], 1);
+        }
+
+        for x in -16..12 {
+            tree.remove(&point![x, 2 * x]);
+        }
+
+        let expected = tree.clone();
+        tree.shrink();
+
+        assert_eq!(tree, expected);
+        assert_eq!(tree.nodes().slots(), tree.nodes().len());
+        assert_eq!(tree.iter().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_clone_shares_nodes() {
+        // Initiate tree
+        let mut tree = GlobalQuadtree::default();
+        tree.insert(point![3, 1], 1);
+        tree.insert(point![3, 3], 1);
+        tree.insert(point![-3, 5], 1);
+        tree.insert(point![-3, 7], 1);
+
+        let snapshot = tree.clone();
+        tree.insert(point![3, 2], 2);
+        tree.remove(&point![-3, 5]);
+
+        // Snapshot is unchanged
+        assert_eq!(snapshot.get(&point![3, 2]), None);
+        assert_eq!(snapshot.get(&point![-3, 5]), Some(&1));
+        assert_eq!(snapshot.population(), 4);
+        assert_eq!(tree.population(), 4);
+    }
+
+    #[test]
+    fn test_iterator() {
+        // Initiate tree
+        let mut tree = Quadtree::default();
+        tree.insert(point![3, 1], 1);
+        tree.insert(point![3, 3], 1);
+        tree.insert(point![3, 5], 1);
+
+        // Inserted points
+        let mut iter = tree.iter();
+
+        assert_eq!(iter.next(), Some((&point![3, 5], &1)));
+        assert_eq!(iter.next(), Some((&point![3, 3], &1)));
+        assert_eq!(iter.next(), Some((&point![3, 1], &1)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_insert_point() {
+        // Initiate tree
+        let mut tree = Quadtree::default();
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 0,
+                children: [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty]
+            }
+        );
+
+        // Insert a point
+        tree.insert(point![3, 1], 1);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 1,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Leaf(point![3, 1], 1)
+                ]
+            }
+        );
+
+        // Create a middle node
+        tree.insert(point![1, 3], 1);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 2,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Node(0),
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[0],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 4 },
+                population: 2,
+                children: [
+                    Tree::Empty,
+                    Tree::Leaf(point![1, 3], 1),
+                    Tree::Leaf(point![3, 1], 1),
+                    Tree::Empty,
+                ]
+            }
+        );
+
+        // Insert in middle node
+        tree.insert(point![3, 3], 1);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 3,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Node(0),
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[0],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 4 },
+                population: 3,
+                children: [
+                    Tree::Empty,
+                    Tree::Leaf(point![1, 3], 1),
+                    Tree::Leaf(point![3, 1], 1),
+                    Tree::Leaf(point![3, 3], 1),
+                ]
+            }
+        );
+
+        // Move the middle node deeper
+        tree.insert(point![3, 5], 1);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 4,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Node(1),
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[1],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 8 },
+                population: 4,
+                children: [
+                    Tree::Node(0),
+                    Tree::Leaf(point![3, 5], 1),
+                    Tree::Empty,
+                    Tree::Empty,
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[0],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 4 },
+                population: 3,
+                children: [
+                    Tree::Empty,
+                    Tree::Leaf(point![1, 3], 1),
+                    Tree::Leaf(point![3, 1], 1),
+                    Tree::Leaf(point![3, 3], 1),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_insert_twice() {
+        // Initiate tree
+        let mut tree = Quadtree::default();
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 0,
+                children: [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty]
+            }
+        );
+
+        // Insert a point
+        tree.insert(point![3, 1], 1);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 1,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Leaf(point![3, 1], 1),
+                ]
+            }
+        );
+
+        // Insert again point
+        tree.insert(point![3, 1], 1);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 1,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Leaf(point![3, 1], 1),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_remove_point() {
+        // Initiate tree
+        let mut tree = Quadtree::default();
+        tree.insert(point![3, 1], 1);
+        tree.insert(point![3, 3], 1);
+        tree.insert(point![1, 3], 1);
+        tree.insert(point![3, 5], 1);
+
+        // Remove point
+        tree.remove(&point![3, 3]);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 3,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Node(1),
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[1],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 8 },
+                population: 3,
+                children: [
+                    Tree::Node(0),
+                    Tree::Leaf(point![3, 5], 1),
+                    Tree::Empty,
+                    Tree::Empty,
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[0],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 4 },
+                population: 2,
+                children: [
+                    Tree::Empty,
+                    Tree::Leaf(point![1, 3], 1),
+                    Tree::Leaf(point![3, 1], 1),
+                    Tree::Empty,
+                ]
+            }
+        );
+
+        // Simplify by moving node up
+        tree.remove(&point![3, 5]);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 2,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Node(0),
+                ]
+            }
+        );
+        assert_eq!(
+            tree.nodes[0],
+            SquareNode {
+                area: BinarySquare { anchor: point![0, 0], size: 4 },
+                population: 2,
+                children: [
+                    Tree::Empty,
+                    Tree::Leaf(point![1, 3], 1),
+                    Tree::Leaf(point![3, 1], 1),
+                    Tree::Empty,
+                ]
+            }
+        );
+        assert_eq!(tree.nodes.len(), 1);
+
+        // Simplify by moving point up
+        tree.remove(&point![1, 3]);
+
+        assert_eq!(
+            tree.root,
+            GlobalNode {
+                population: 1,
+                children: [
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Empty,
+                    Tree::Leaf(point![3, 1], 1),
+                ]
+            }
+        );
+        assert!(tree.nodes.is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+use std::ops::{Index, IndexMut};
+use std::rc::Rc;
+use crate::coordinate::Coordinate;
+use crate::quadtree::square_node::SquareNode;
+use crate::quadtree::tree::Tree;
+
+/// Index of a node inside an arena
+pub type NodeId = u32;
+
+/// Number of slots per chunk
+const CHUNK_SIZE: usize = 64;
+
+/// Slots of a chunk, each node being shared between clones
+type Chunk<T, C> = Vec<Option<Rc<SquareNode<T, C>>>>;
+
+/// Node storage, reusing slots of removed nodes.
+/// Chunks, nodes and the free list are shared between clones, and copied on write:
+/// cloning costs O(1), and an edit only copies the nodes it changes along with pointers to their siblings.
+#[derive(Debug)]
+pub struct Arena<T, C: Coordinate = i32> {
+    chunks: Rc<Vec<Rc<Chunk<T, C>>>>,
+    slots: usize,
+    free: Rc<Vec<NodeId>>,
+}
+
+// Methods
+impl<T, C: Coordinate> Arena<T, C> {
+    /// Creates an empty arena
+    #[inline]
+    pub fn new() -> Arena<T, C> {
+        Arena {
+            chunks: Rc::new(Vec::new()),
+            slots: 0,
+            free: Rc::new(Vec::new()),
+        }
+    }
+
+    /// Number of stored nodes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.slots - self.free.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of slots, including free ones
+    #[inline]
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Removes all nodes
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Arena::new();
+    }
+}
+
+impl<T: Clone, C: Coordinate> Arena<T, C> {
+    /// Stores node, reusing a free slot if any
+    pub fn alloc(&mut self, node: SquareNode<T, C>) -> NodeId {
+        let free = if self.free.is_empty() { None } else { Rc::make_mut(&mut self.free).pop() };
+
+        match free {
+            Some(id) => {
+                *self.slot_mut(id) = Some(Rc::new(node));
+                id
+            },
+            None => {
+                let chunks = Rc::make_mut(&mut self.chunks);
+
+                if self.slots == chunks.len() * CHUNK_SIZE {
+                    chunks.push(Rc::new(Vec::with_capacity(CHUNK_SIZE)));
+                }
+
+                let chunk = chunks.last_mut().unwrap();
+                Rc::make_mut(chunk).push(Some(Rc::new(node)));
+
+                self.slots += 1;
+                (self.slots - 1) as NodeId
+            }
+        }
+    }
+
+    /// Removes node from arena, its slot being reused by next allocations
+    pub fn free(&mut self, id: NodeId) -> SquareNode<T, C> {
+        let node = self.slot_mut(id).take().expect("node was already freed");
+        Rc::make_mut(&mut self.free).push(id);
+
+        Rc::unwrap_or_clone(node)
+    }
+
+    /// Iterates mutably over stored nodes
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SquareNode<T, C>> {
+        Rc::make_mut(&mut self.chunks).iter_mut()
+            .flat_map(|chunk| Rc::make_mut(chunk).iter_mut())
+            .flatten()
+            .map(Rc::make_mut)
+    }
+
+    /// Moves nodes reachable from given trees at the beginning of the arena, in depth first order,
+    /// dropping all free slots and releasing unused memory
+    pub fn compact<'a>(&mut self, trees: impl Iterator<Item = &'a mut Tree<T, C>>) where T: 'a {
+        let mut compacted = Arena::new();
+
+        for tree in trees {
+            self.move_tree(tree, &mut compacted);
+        }
+
+        let chunks = Rc::make_mut(&mut compacted.chunks);
+
+        if let Some(chunk) = chunks.last_mut() {
+            Rc::make_mut(chunk).shrink_to_fit();
+        }
+
+        chunks.shrink_to_fit();
+        *self = compacted;
+    }
+
+    /// Removes all nodes of given tree
+    pub fn free_tree(&mut self, tree: &Tree<T, C>) {
+        if let &Tree::Node(id) = tree {
+            let node = self.free(id);
+
+            for child in &node.children {
+                self.free_tree(child);
+            }
+        }
+    }
+
+    /// Moves all nodes of given tree into target arena, updating their ids
+    pub fn move_tree(&mut self, tree: &mut Tree<T, C>, target: &mut Arena<T, C>) {
+        if let Tree::Node(id) = tree {
+            let mut node = self.free(*id);
+
+            for child in node.children.iter_mut() {
+                self.move_tree(child, target);
+            }
+
+            *id = target.alloc(node);
+        }
+    }
+
+    /// Slot of given node, copying the chunk list and its chunk if shared
+    #[inline]
+    fn slot_mut(&mut self, id: NodeId) -> &mut Option<Rc<SquareNode<T, C>>> {
+        let id = id as usize;
+        let chunk = &mut Rc::make_mut(&mut self.chunks)[id / CHUNK_SIZE];
+
+        &mut Rc::make_mut(chunk)[id % CHUNK_SIZE]
+    }
+}
+
+// Utils
+impl<T, C: Coordinate> Clone for Arena<T, C> {
+    /// Shares chunks, nodes and free list with the clone
+    fn clone(&self) -> Self {
+        Arena {
+            chunks: Rc::clone(&self.chunks),
+            slots: self.slots,
+            free: Rc::clone(&self.free),
+        }
+    }
+}
+
+impl<T, C: Coordinate> Default for Arena<T, C> {
+    #[inline]
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T, C: Coordinate> Index<NodeId> for Arena<T, C> {
+    type Output = SquareNode<T, C>;
+
+    #[inline]
+    fn index(&self, id: NodeId) -> &Self::Output {
+        let id = id as usize;
+        self.chunks[id / CHUNK_SIZE][id % CHUNK_SIZE].as_deref().expect("node was freed")
+    }
+}
+
+impl<T: Clone, C: Coordinate> IndexMut<NodeId> for Arena<T, C> {
+    #[inline]
+    fn index_mut(&mut self, id: NodeId) -> &mut Self::Output {
+        Rc::make_mut(self.slot_mut(id).as_mut().expect("node was freed"))
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use na::point;
+    use crate::quadtree::binary_square::BinarySquare;
+    use super::*;
+
+    fn node(size: u64) -> SquareNode<u8> {
+        SquareNode::new(BinarySquare { anchor: point![0, 0], size })
+    }
+
+    #[test]
+    fn test_alloc_reuses_free_slots() {
+        let mut arena = Arena::new();
+
+        let a = arena.alloc(node(2));
+        let b = arena.alloc(node(4));
+
+        assert_eq!(arena.free(a), node(2));
+        assert_eq!(arena.len(), 1);
+        assert_eq!(arena.alloc(node(8)), a);
+        assert_eq!(arena[a], node(8));
+        assert_eq!(arena[b], node(4));
+        assert_eq!(arena.slots(), 2);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut arena = Arena::new();
+
+        let mut leaf = node(2);
+        leaf.children[0] = Tree::Leaf(point![0, 0], 1);
+
+        arena.alloc(node(16));
+        let leaf = arena.alloc(leaf);
+
+        let mut upper = node(4);
+        upper.children[0] = Tree::Node(leaf);
+
+        let mut trees = [Tree::Node(arena.alloc(upper))];
+        arena.free(0);
+
+        arena.compact(trees.iter_mut());
+
+        assert_eq!(arena.slots(), 2);
+        assert_eq!(trees, [Tree::Node(1)]);
+        assert_eq!(arena[1].children[0], Tree::Node(0));
+        assert_eq!(arena[0].children[0], Tree::Leaf(point![0, 0], 1));
+    }
+
+    #[test]
+    fn test_clone_shares_nodes() {
+        let mut arena = Arena::new();
+        let ids = (0..3 * CHUNK_SIZE as u64).map(|size| arena.alloc(node(size))).collect::<Vec<_>>();
+        arena.free(ids[0]);
+
+        let snapshot = arena.clone();
+        arena[ids[CHUNK_SIZE]].population = 5;
+
+        // Snapshot is unchanged
+        assert_eq!(snapshot[ids[CHUNK_SIZE]].population, 0);
+        assert_eq!(arena[ids[CHUNK_SIZE]].population, 5);
+
+        // Only the modified node and its chunk of pointers are copied
+        let shared = arena.chunks.iter().zip(snapshot.chunks.iter())
+            .map(|(lhs, rhs)| Rc::ptr_eq(lhs, rhs))
+            .collect::<Vec<_>>();
+
+        assert_eq!(shared, [true, false, true]);
+
+        let slot = |arena: &Arena<u8>, id: NodeId| arena.chunks[id as usize / CHUNK_SIZE][id as usize % CHUNK_SIZE].clone().unwrap();
+
+        assert!(!Rc::ptr_eq(&slot(&arena, ids[CHUNK_SIZE]), &slot(&snapshot, ids[CHUNK_SIZE])));
+        assert!(Rc::ptr_eq(&slot(&arena, ids[CHUNK_SIZE + 1]), &slot(&snapshot, ids[CHUNK_SIZE + 1])));
+
+        // Free list is only copied when changed
+        assert!(Rc::ptr_eq(&arena.free, &snapshot.free));
+
+        arena.alloc(node(1));
+        assert!(!Rc::ptr_eq(&arena.free, &snapshot.free));
+        assert_eq!(snapshot.free.as_slice(), [ids[0]]);
+    }
+}
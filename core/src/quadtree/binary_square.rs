@@ -1,23 +1,23 @@
 use std::cmp::{max, min};
 use std::mem;
 use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
-use na::{Point, point, Point2};
-use py::{Holds, Overlaps, PointBounds};
-use py::traits::DimensionBounds;
+use na::{point, Point2};
+use py::{Holds, Overlaps};
+use crate::coordinate::Coordinate;
 use crate::quadtree::quarter::{global_quarter, Quarter};
 
 /// Square with a power of 2 as size
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct BinarySquare {
-    pub anchor: Point2<i32>,
-    pub size: u32,
+pub struct BinarySquare<C: Coordinate = i32> {
+    pub anchor: Point2<C>,
+    pub size: u64,
 }
 
 // Methods
-impl BinarySquare {
-    fn search_area(start: Point2<i32>, end: Point2<i32>, mut bits: u32) -> BinarySquare {
-        while bits < u32::BITS {
-            let mask = (u32::MAX << bits) as i32;
+impl<C: Coordinate> BinarySquare<C> {
+    fn search_area(start: Point2<C>, end: Point2<C>, mut bits: u32) -> BinarySquare<C> {
+        while bits < C::BITS {
+            let mask = C::high_mask(bits);
 
             let square = BinarySquare {
                 anchor: point![start.x & mask, start.y & mask],
@@ -32,11 +32,11 @@ impl BinarySquare {
             }
         }
 
-        panic!("unable to find matching binary square");
+        unreachable!("points of the same global quarter always have a common square");
     }
 
     /// Returns smallest square containing given squares
-    pub fn common(rhs: &BinarySquare, lhs: &BinarySquare) -> Result<BinarySquare, &'static str> {
+    pub fn common(rhs: &BinarySquare<C>, lhs: &BinarySquare<C>) -> Result<BinarySquare<C>, &'static str> {
         if global_quarter(&rhs.anchor) != global_quarter(&lhs.anchor) {
             Err("given squares does not belong to the same global quarter")
         } else {
@@ -52,16 +52,24 @@ impl BinarySquare {
     }
 
     #[inline]
-    pub fn wrapping(point: Point2<i32>) -> BinarySquare {
+    pub fn wrapping(point: Point2<C>) -> BinarySquare<C> {
         BinarySquare {
             anchor: point,
             size: 1,
         }
     }
 
+    /// Returns last point of the square (opposite to anchor)
+    #[inline]
+    pub fn last_point(&self) -> Point2<C> {
+        let last = C::from_bits(self.size - 1);
+
+        point![self.anchor.x + last, self.anchor.y + last]
+    }
+
     /// Returns quarter containing point
-    pub fn quarter(&self, point: &Point2<i32>) -> Quarter {
-        let mask = (self.size >> 1) as i32;
+    pub fn quarter(&self, point: &Point2<C>) -> Quarter {
+        let mask = C::from_bits(self.size >> 1);
         let mut quarter = 0u8;
 
         if (point.x & mask) == mask {
@@ -77,95 +85,70 @@ impl BinarySquare {
 }
 
 // Utils
-impl DimensionBounds<i32, 2> for BinarySquare {
-    type Output = Range<i32>;
-
-    unsafe fn get_bounds_unchecked(&self, idx: usize) -> Self::Output {
-        let start = *self.anchor.get_unchecked(idx);
-
-        start..(start + self.size as i32)
-    }
-}
-
-impl PointBounds<i32, 2> for BinarySquare {
-    #[inline]
-    fn start_point(&self) -> Option<Point<i32, 2>> {
-        Some(self.anchor)
-    }
-
-    #[inline]
-    fn end_point(&self) -> Option<Point<i32, 2>> {
-        let size = self.size as i32;
-
-        Some(point![self.anchor.x + size, self.anchor.y + size])
-    }
-}
-
-impl Holds<Point2<i32>> for BinarySquare {
-    fn holds(&self, object: &Point2<i32>) -> bool {
-        let left = self.size as i32;
-
+impl<C: Coordinate> Holds<Point2<C>> for BinarySquare<C> {
+    fn holds(&self, object: &Point2<C>) -> bool {
+        // Compared as unsigned, as the biggest squares have a size greater than the coordinate maximum
         self.anchor.iter()
             .zip(object.iter())
-            .all(|(a, o)| (a ^ o) < left)
+            .all(|(&a, &o)| (a ^ o).to_bits() < self.size)
     }
 }
 
-impl Holds<BinarySquare> for BinarySquare {
+impl<C: Coordinate> Holds<BinarySquare<C>> for BinarySquare<C> {
     #[inline]
-    fn holds(&self, object: &BinarySquare) -> bool {
+    fn holds(&self, object: &BinarySquare<C>) -> bool {
         object.size <= self.size && self.holds(&object.anchor)
     }
 }
 
-impl Overlaps<BinarySquare> for Range<Point2<i32>> {
+impl<C: Coordinate> Overlaps<BinarySquare<C>> for Range<Point2<C>> {
     #[inline]
-    fn overlaps(&self, lhs: &BinarySquare) -> bool {
-        self.overlaps(&Range::from(lhs))
+    fn overlaps(&self, lhs: &BinarySquare<C>) -> bool {
+        self.overlaps(&RangeInclusive::from(lhs))
     }
 }
 
-impl Overlaps<BinarySquare> for RangeFrom<Point2<i32>> {
+impl<C: Coordinate> Overlaps<BinarySquare<C>> for RangeFrom<Point2<C>> {
     #[inline]
-    fn overlaps(&self, lhs: &BinarySquare) -> bool {
-        self.overlaps(&Range::from(lhs))
+    fn overlaps(&self, lhs: &BinarySquare<C>) -> bool {
+        self.overlaps(&RangeInclusive::from(lhs))
     }
 }
 
-impl Overlaps<BinarySquare> for RangeFull {
+impl<C: Coordinate> Overlaps<BinarySquare<C>> for RangeFull {
     #[inline]
-    fn overlaps(&self, _: &BinarySquare) -> bool {
+    fn overlaps(&self, _: &BinarySquare<C>) -> bool {
         true
     }
 }
 
-impl Overlaps<BinarySquare> for RangeInclusive<Point2<i32>> {
+impl<C: Coordinate> Overlaps<BinarySquare<C>> for RangeInclusive<Point2<C>> {
     #[inline]
-    fn overlaps(&self, lhs: &BinarySquare) -> bool {
-        self.overlaps(&Range::from(lhs))
+    fn overlaps(&self, lhs: &BinarySquare<C>) -> bool {
+        self.overlaps(&RangeInclusive::from(lhs))
     }
 }
 
-impl Overlaps<BinarySquare> for RangeTo<Point2<i32>> {
+impl<C: Coordinate> Overlaps<BinarySquare<C>> for RangeTo<Point2<C>> {
     #[inline]
-    fn overlaps(&self, lhs: &BinarySquare) -> bool {
-        self.overlaps(&Range::from(lhs))
+    fn overlaps(&self, lhs: &BinarySquare<C>) -> bool {
+        self.overlaps(&RangeInclusive::from(lhs))
     }
 }
 
-impl Overlaps<BinarySquare> for RangeToInclusive<Point2<i32>> {
+impl<C: Coordinate> Overlaps<BinarySquare<C>> for RangeToInclusive<Point2<C>> {
     #[inline]
-    fn overlaps(&self, lhs: &BinarySquare) -> bool {
-        self.overlaps(&Range::from(lhs))
+    fn overlaps(&self, lhs: &BinarySquare<C>) -> bool {
+        self.overlaps(&RangeInclusive::from(lhs))
     }
 }
 
 // Conversion
-impl From<&BinarySquare> for Range<Point2<i32>> {
-    fn from(value: &BinarySquare) -> Self {
-        let size = value.size as i32;
-
-        value.anchor..(point![value.anchor.x + size, value.anchor.y + size])
+impl<C: Coordinate> From<&BinarySquare<C>> for RangeInclusive<Point2<C>> {
+    /// Last point is included, as the end of the biggest squares cannot be represented
+    #[inline]
+    fn from(value: &BinarySquare<C>) -> Self {
+        value.anchor..=value.last_point()
     }
 }
 
@@ -217,6 +200,22 @@ mod tests {
                 Ok(BinarySquare { anchor: point![128, 40], size: 8 })
             );
         }
+
+        #[test]
+        fn test_limits() {
+            assert_eq!(
+                BinarySquare::common(&BinarySquare::wrapping(point![0, 0]), &BinarySquare::wrapping(point![i32::MAX, 5])),
+                Ok(BinarySquare { anchor: point![0, 0], size: 1 << 31 })
+            );
+            assert_eq!(
+                BinarySquare::common(&BinarySquare::wrapping(point![-1, -1]), &BinarySquare::wrapping(point![i32::MIN, -1])),
+                Ok(BinarySquare { anchor: point![i32::MIN, i32::MIN], size: 1 << 31 })
+            );
+            assert_eq!(
+                BinarySquare::common(&BinarySquare::wrapping(point![0i64, 0]), &BinarySquare::wrapping(point![i64::MAX, 0])),
+                Ok(BinarySquare { anchor: point![0, 0], size: 1 << 63 })
+            );
+        }
     }
 
     mod quarter {
@@ -304,5 +303,16 @@ mod tests {
             assert!(square.holds(&point![-4, -3]));
             assert!(square.holds(&point![-3, -3]));
         }
+
+        #[test]
+        fn test_biggest_square() {
+            let square = BinarySquare { anchor: point![i32::MIN, 0], size: 1 << 31 };
+
+            assert!(square.holds(&point![i32::MIN, i32::MAX]));
+            assert!(square.holds(&point![-1, 0]));
+            assert!(!square.holds(&point![0, 0]));
+            assert_eq!(square.last_point(), point![-1, i32::MAX]);
+            assert_eq!(square.quarter(&point![-1, 0]), Quarter::SouthEast);
+        }
     }
 }
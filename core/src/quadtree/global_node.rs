@@ -0,0 +1,66 @@
+use std::slice::{Iter, IterMut};
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::quadtree::node::Node;
+use crate::quadtree::quarter::global_quarter;
+use crate::quadtree::tree::Tree;
+
+/// Quadtree global node
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GlobalNode<T, C: Coordinate = i32> {
+    pub population: usize,
+    pub children: [Tree<T, C>; 4],
+}
+
+impl<T, C: Coordinate> GlobalNode<T, C> {
+    /// Create a new empty node
+    #[inline]
+    pub fn new() -> GlobalNode<T, C> {
+        GlobalNode {
+            population: 0,
+            children: [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty],
+        }
+    }
+}
+
+// Utils
+impl<T, C: Coordinate> Default for GlobalNode<T, C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Coordinate> Node<T, C> for GlobalNode<T, C> {
+    #[inline]
+    fn children(&self) -> Iter<'_, Tree<T, C>> {
+        self.children.iter()
+    }
+
+    #[inline]
+    fn children_mut(&mut self) -> IterMut<'_, Tree<T, C>> {
+        self.children.iter_mut()
+    }
+
+    #[inline]
+    fn population(&self) -> usize {
+        self.population
+    }
+
+    #[inline]
+    fn population_mut(&mut self) -> &mut usize {
+        &mut self.population
+    }
+
+    #[inline]
+    fn child_holding(&self, point: &Point2<C>) -> &Tree<T, C> {
+        let idx = global_quarter(point) as usize;
+        unsafe { self.children.get_unchecked(idx) }
+    }
+
+    #[inline]
+    fn child_holding_mut(&mut self, point: &Point2<C>) -> &mut Tree<T, C> {
+        let idx = global_quarter(point) as usize;
+        unsafe { self.children.get_unchecked_mut(idx) }
+    }
+}
@@ -0,0 +1,37 @@
+use std::iter::FusedIterator;
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::quadtree::arena::Arena;
+use crate::quadtree::node::Node;
+use crate::quadtree::tree::Tree;
+
+pub struct Iter<'a, T, C: Coordinate = i32> {
+    nodes: &'a Arena<T, C>,
+    stack: Vec<&'a Tree<T, C>>,
+}
+
+impl<'a, T, C: Coordinate> Iter<'a, T, C> {
+    pub fn new<N: Node<T, C>>(root: &'a N, nodes: &'a Arena<T, C>) -> Iter<'a, T, C> {
+        Iter {
+            nodes,
+            stack: root.children().collect()
+        }
+    }
+}
+
+impl<'a, T, C: Coordinate> Iterator for Iter<'a, T, C> {
+    type Item = (&'a Point2<C>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop() {
+                None => return None,
+                Some(Tree::Empty) => (),
+                Some(Tree::Leaf(pt, value)) => return Some((pt, value)),
+                Some(&Tree::Node(id)) => self.stack.extend(&self.nodes[id].children),
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Coordinate> FusedIterator for Iter<'a, T, C> {}
@@ -0,0 +1,314 @@
+use std::mem;
+use std::slice::{Iter, IterMut};
+use std::cmp::{max, min};
+use na::{point, Point2};
+use py::{Holds, Overlaps};
+use crate::coordinate::Coordinate;
+use crate::quadtree::arena::{Arena, NodeId};
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::square_node::SquareNode;
+use crate::quadtree::tree::Tree;
+
+/// Quadtree node, children nodes being stored in given arena
+pub trait Node<T, C: Coordinate = i32> {
+    fn children(&self) -> Iter<'_, Tree<T, C>>;
+
+    fn children_mut(&mut self) -> IterMut<'_, Tree<T, C>>;
+
+    /// Number of points held by node
+    fn population(&self) -> usize;
+
+    fn population_mut(&mut self) -> &mut usize;
+
+    fn child_holding(&self, point: &Point2<C>) -> &Tree<T, C>;
+
+    fn child_holding_mut(&mut self, point: &Point2<C>) -> &mut Tree<T, C>;
+
+    /// Test if node contains point
+    fn has(&self, nodes: &Arena<T, C>, point: &Point2<C>) -> bool {
+        self.get(nodes, point).is_some()
+    }
+
+    /// Returns value of point, if contained by node
+    fn get<'a>(&'a self, nodes: &'a Arena<T, C>, point: &Point2<C>) -> Option<&'a T> {
+        match self.child_holding(point) {
+            Tree::Empty => None,
+            Tree::Leaf(pt, value) => (point == pt).then_some(value),
+            &Tree::Node(id) => {
+                let child = &nodes[id];
+                child.area.holds(point).then(|| child.get(nodes, point)).flatten()
+            },
+        }
+    }
+
+    /// Returns mutable reference to value of point, if contained by node
+    fn get_mut<'a>(&'a mut self, nodes: &'a mut Arena<T, C>, point: &Point2<C>) -> Option<&'a mut T> where T: Clone {
+        let mut id = match self.child_holding_mut(point) {
+            Tree::Leaf(pt, value) if pt == point => return Some(value),
+            &mut Tree::Node(id) => id,
+            _ => return None,
+        };
+
+        // Search node holding point's leaf
+        loop {
+            let node = &nodes[id];
+
+            if !node.area.holds(point) {
+                return None;
+            }
+
+            match node.child_holding(point) {
+                &Tree::Node(child) => id = child,
+                Tree::Leaf(pt, _) if pt == point => break,
+                _ => return None,
+            }
+        }
+
+        match nodes[id].child_holding_mut(point) {
+            Tree::Leaf(_, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Counts points inside given area, using cached populations of nodes fully inside it
+    fn count_in<B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(&self, nodes: &Arena<T, C>, area: &B) -> usize {
+        self.children()
+            .map(|child| match child {
+                Tree::Empty => 0,
+                Tree::Leaf(pt, _) => area.holds(pt) as usize,
+                &Tree::Node(id) => {
+                    let node = &nodes[id];
+
+                    if area.holds(&node.area.anchor) && area.holds(&node.area.last_point()) {
+                        node.population
+                    } else if area.overlaps(&node.area) {
+                        node.count_in(nodes, area)
+                    } else {
+                        0
+                    }
+                },
+            })
+            .sum()
+    }
+
+    /// Extends given bounds (first and last points) to include all points of node.
+    /// Nodes already inside bounds are skipped.
+    fn extend_bounds(&self, nodes: &Arena<T, C>, bounds: &mut Option<(Point2<C>, Point2<C>)>) {
+        for child in self.children() {
+            match child {
+                Tree::Empty => (),
+                Tree::Leaf(pt, _) => extend(bounds, pt),
+                &Tree::Node(id) => {
+                    let node = &nodes[id];
+                    let inside = bounds.is_some_and(|(first, last)| {
+                        let area = first..=last;
+                        area.holds(&node.area.anchor) && area.holds(&node.area.last_point())
+                    });
+
+                    if !inside {
+                        node.extend_bounds(nodes, bounds);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Search greatest node matching area
+    fn search<'a>(&'a self, nodes: &'a Arena<T, C>, area: &BinarySquare<C>) -> Option<&'a Tree<T, C>> {
+        let tree = self.child_holding(&area.anchor);
+
+        match tree {
+            Tree::Empty => None,
+            Tree::Leaf(pt, _) => {
+                if area.holds(pt) {
+                    Some(tree)
+                } else {
+                    None
+                }
+            },
+            &Tree::Node(id) => {
+                let child = &nodes[id];
+
+                if area.holds(&child.area) {
+                    Some(tree)
+                } else {
+                    child.search(nodes, area)
+                }
+            },
+        }
+    }
+
+    /// Inserts value at given point, returning previous value of point
+    fn insert(&mut self, nodes: &mut Arena<T, C>, point: Point2<C>, value: T) -> Option<T> where T: Clone {
+        let pos = self.child_holding_mut(&point);
+
+        let previous = match pos {
+            Tree::Empty => {
+                *pos = Tree::Leaf(point, value);
+                None
+            },
+            Tree::Leaf(pt, previous) if *pt == point => Some(mem::replace(previous, value)),
+            &mut Tree::Leaf(pt, _) => {
+                insert_upper(nodes, pos, &BinarySquare::wrapping(pt), point, value);
+                None
+            },
+            &mut Tree::Node(id) => {
+                let area = nodes[id].area;
+
+                if area.holds(&point) {
+                    // Node is taken out of its slot while updated, an empty node holding its place
+                    let mut node = mem::replace(&mut nodes[id], SquareNode::new(area));
+                    let previous = node.insert(nodes, point, value);
+
+                    nodes[id] = node;
+                    previous
+                } else {
+                    insert_upper(nodes, pos, &area, point, value);
+                    None
+                }
+            }
+        };
+
+        if previous.is_none() {
+            *self.population_mut() += 1;
+        }
+
+        previous
+    }
+
+    /// Removes point from node, returning its value if it was present
+    fn remove(&mut self, nodes: &mut Arena<T, C>, point: &Point2<C>) -> Option<T> where T: Clone {
+        let pos = self.child_holding_mut(point);
+
+        let removed = match *pos {
+            Tree::Leaf(pt, _) if &pt == point => match mem::replace(pos, Tree::Empty) {
+                Tree::Leaf(_, value) => Some(value),
+                _ => unreachable!(),
+            },
+            Tree::Node(id) if nodes[id].area.holds(point) => {
+                let area = nodes[id].area;
+                let mut node = mem::replace(&mut nodes[id], SquareNode::new(area));
+                let removed = node.remove(nodes, point);
+
+                *pos = simplify_in_place(nodes, id, node);
+
+                removed
+            }
+            _ => None,
+        };
+
+        if removed.is_some() {
+            *self.population_mut() -= 1;
+        }
+
+        removed
+    }
+
+    /// Removes points inside given area, returning their count.
+    /// Subtrees fully inside area are pruned at once: moved into target arena if any, freed otherwise.
+    /// With a target, also returns removed points as one tree per child, stored in target arena.
+    fn remove_area<B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(&mut self, nodes: &mut Arena<T, C>, area: &B, mut target: Option<&mut Arena<T, C>>) -> (usize, [Tree<T, C>; 4]) where T: Clone {
+        let mut removed = [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty];
+        let mut count = 0;
+
+        for (child, removed) in self.children_mut().zip(&mut removed) {
+            let (child_count, child_removed) = remove_tree_area(nodes, child, area, target.as_deref_mut());
+
+            count += child_count;
+            *removed = child_removed;
+        }
+
+        *self.population_mut() -= count;
+
+        (count, removed)
+    }
+}
+
+// Utils
+/// Extends bounds to include given point
+fn extend<C: Coordinate>(bounds: &mut Option<(Point2<C>, Point2<C>)>, point: &Point2<C>) {
+    *bounds = Some(match *bounds {
+        None => (*point, *point),
+        Some((first, last)) => (
+            point![min(first.x, point.x), min(first.y, point.y)],
+            point![max(last.x, point.x), max(last.y, point.y)],
+        ),
+    });
+}
+
+/// Removes points of tree inside given area, returning their count and, with a target, the tree holding them
+fn remove_tree_area<T: Clone, C: Coordinate, B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>>(nodes: &mut Arena<T, C>, tree: &mut Tree<T, C>, area: &B, mut target: Option<&mut Arena<T, C>>) -> (usize, Tree<T, C>) {
+    match *tree {
+        Tree::Empty => (0, Tree::Empty),
+        Tree::Leaf(pt, _) if area.holds(&pt) => {
+            let leaf = mem::replace(tree, Tree::Empty);
+            (1, if target.is_some() { leaf } else { Tree::Empty })
+        },
+        Tree::Leaf(..) => (0, Tree::Empty),
+        Tree::Node(id) => {
+            let square = nodes[id].area;
+
+            if area.holds(&square.anchor) && area.holds(&square.last_point()) {
+                let count = nodes[id].population;
+
+                match target {
+                    Some(target) => {
+                        nodes.move_tree(tree, target);
+                        (count, mem::replace(tree, Tree::Empty))
+                    },
+                    None => {
+                        nodes.free_tree(tree);
+                        *tree = Tree::Empty;
+                        (count, Tree::Empty)
+                    },
+                }
+            } else if area.overlaps(&square) {
+                let mut node = mem::replace(&mut nodes[id], SquareNode::new(square));
+                let (count, removed) = node.remove_area(nodes, area, target.as_deref_mut());
+
+                *tree = simplify_in_place(nodes, id, node);
+
+                let removed = match target {
+                    Some(target) => simplify(target, SquareNode { area: square, population: count, children: removed }),
+                    None => Tree::Empty,
+                };
+
+                (count, removed)
+            } else {
+                (0, Tree::Empty)
+            }
+        },
+    }
+}
+
+/// Stores node in arena, or moves up its only child
+fn simplify<T: Clone, C: Coordinate>(nodes: &mut Arena<T, C>, node: SquareNode<T, C>) -> Tree<T, C> {
+    match node.children.iter().filter(|child| !child.is_empty()).count() {
+        0 => Tree::Empty,
+        1 => node.children.into_iter().find(|child| !child.is_empty()).unwrap(),
+        _ => Tree::Node(nodes.alloc(node)),
+    }
+}
+
+/// Stores node back in its slot, or frees the slot and moves up its only child
+fn simplify_in_place<T: Clone, C: Coordinate>(nodes: &mut Arena<T, C>, id: NodeId, node: SquareNode<T, C>) -> Tree<T, C> {
+    if node.children.iter().filter(|child| !child.is_empty()).count() > 1 {
+        nodes[id] = node;
+        Tree::Node(id)
+    } else {
+        nodes.free(id);
+        simplify(nodes, node)
+    }
+}
+
+/// Replaces tree at pos (covering area) by a new node, holding both previous tree and the new point
+fn insert_upper<T: Clone, C: Coordinate>(nodes: &mut Arena<T, C>, pos: &mut Tree<T, C>, area: &BinarySquare<C>, point: Point2<C>, value: T) {
+    let mut upper = SquareNode::new(BinarySquare::common(area, &BinarySquare::wrapping(point)).unwrap());
+    let previous = mem::replace(pos, Tree::Empty);
+
+    upper.population = previous.population(nodes);
+    *upper.child_holding_mut(&area.anchor) = previous;
+    upper.insert(nodes, point, value);
+
+    *pos = Tree::Node(nodes.alloc(upper));
+}
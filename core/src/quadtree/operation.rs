@@ -0,0 +1,191 @@
+use std::array;
+use py::Holds;
+use crate::coordinate::Coordinate;
+use crate::quadtree::arena::Arena;
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::square_node::SquareNode;
+use crate::quadtree::tree::Tree;
+
+/// Set operation between the points of two trees
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl SetOperation {
+    /// Test if points only held by left tree are kept
+    #[inline]
+    fn keeps_lhs(&self) -> bool {
+        matches!(self, SetOperation::Union | SetOperation::Difference | SetOperation::SymmetricDifference)
+    }
+
+    /// Test if points only held by right tree are kept
+    #[inline]
+    fn keeps_rhs(&self) -> bool {
+        matches!(self, SetOperation::Union | SetOperation::SymmetricDifference)
+    }
+
+    /// Test if points held by both trees are kept
+    #[inline]
+    fn keeps_both(&self) -> bool {
+        matches!(self, SetOperation::Union | SetOperation::Intersection)
+    }
+}
+
+/// Combines two trees, recursing over matching squares.
+/// Resulting nodes are stored in a new arena, values of left tree being kept for points held by both trees.
+pub struct Combinator<'a, T, C: Coordinate = i32> {
+    operation: SetOperation,
+    lhs: &'a Arena<T, C>,
+    rhs: &'a Arena<T, C>,
+    nodes: Arena<T, C>,
+}
+
+// Methods
+impl<'a, T: Clone, C: Coordinate> Combinator<'a, T, C> {
+    #[inline]
+    pub fn new(operation: SetOperation, lhs: &'a Arena<T, C>, rhs: &'a Arena<T, C>) -> Combinator<'a, T, C> {
+        Combinator {
+            operation,
+            lhs,
+            rhs,
+            nodes: Arena::new(),
+        }
+    }
+
+    /// Returns arena holding resulting nodes
+    #[inline]
+    pub fn into_nodes(self) -> Arena<T, C> {
+        self.nodes
+    }
+
+    /// Combines given trees. Both must belong to the same global quarter.
+    pub fn combine(&mut self, lhs: &'a Tree<T, C>, rhs: &'a Tree<T, C>) -> Tree<T, C> {
+        let (lhs_nodes, rhs_nodes) = (self.lhs, self.rhs);
+
+        match (area(lhs, lhs_nodes), area(rhs, rhs_nodes)) {
+            (None, None) => Tree::Empty,
+            (Some(_), None) => self.keep(self.operation.keeps_lhs(), lhs, lhs_nodes),
+            (None, Some(_)) => self.keep(self.operation.keeps_rhs(), rhs, rhs_nodes),
+            (Some(lhs_area), Some(rhs_area)) if lhs_area == rhs_area => match (lhs, rhs) {
+                (Tree::Leaf(pt, value), _) => {
+                    if self.operation.keeps_both() {
+                        Tree::Leaf(*pt, value.clone())
+                    } else {
+                        Tree::Empty
+                    }
+                },
+                (&Tree::Node(lhs), &Tree::Node(rhs)) => {
+                    let (lhs, rhs) = (&lhs_nodes[lhs].children, &rhs_nodes[rhs].children);
+                    let children = array::from_fn(|idx| self.combine(&lhs[idx], &rhs[idx]));
+
+                    self.simplify(lhs_area, children)
+                },
+                _ => unreachable!("squares of same size hold trees of same kind"),
+            },
+            (Some(lhs_area), Some(rhs_area)) if lhs_area.holds(&rhs_area) => {
+                let &Tree::Node(id) = lhs else { unreachable!() };
+                let children = &lhs_nodes[id].children;
+                let quarter = lhs_area.quarter(&rhs_area.anchor) as usize;
+
+                let children = array::from_fn(|idx| {
+                    if idx == quarter {
+                        self.combine(&children[idx], rhs)
+                    } else {
+                        self.combine(&children[idx], &Tree::Empty)
+                    }
+                });
+
+                self.simplify(lhs_area, children)
+            },
+            (Some(lhs_area), Some(rhs_area)) if rhs_area.holds(&lhs_area) => {
+                let &Tree::Node(id) = rhs else { unreachable!() };
+                let children = &rhs_nodes[id].children;
+                let quarter = rhs_area.quarter(&lhs_area.anchor) as usize;
+
+                let children = array::from_fn(|idx| {
+                    if idx == quarter {
+                        self.combine(lhs, &children[idx])
+                    } else {
+                        self.combine(&Tree::Empty, &children[idx])
+                    }
+                });
+
+                self.simplify(rhs_area, children)
+            },
+            _ => {
+                // Disjoint squares
+                let lhs = self.combine(lhs, &Tree::Empty);
+                let rhs = self.combine(&Tree::Empty, rhs);
+
+                self.merge(lhs, rhs)
+            },
+        }
+    }
+
+    // Utils
+    fn keep(&mut self, keep: bool, tree: &Tree<T, C>, nodes: &Arena<T, C>) -> Tree<T, C> {
+        if keep {
+            self.copy(tree, nodes)
+        } else {
+            Tree::Empty
+        }
+    }
+
+    /// Copies tree from given arena into the resulting one
+    fn copy(&mut self, tree: &Tree<T, C>, nodes: &Arena<T, C>) -> Tree<T, C> {
+        match tree {
+            &Tree::Node(id) => {
+                let node = &nodes[id];
+                let copy = SquareNode {
+                    area: node.area,
+                    population: node.population,
+                    children: array::from_fn(|idx| self.copy(&node.children[idx], nodes)),
+                };
+
+                Tree::Node(self.nodes.alloc(copy))
+            },
+            tree => tree.clone(),
+        }
+    }
+
+    /// Builds node from given children, moving up a lonely child
+    fn simplify(&mut self, area: BinarySquare<C>, children: [Tree<T, C>; 4]) -> Tree<T, C> {
+        match children.iter().filter(|child| !child.is_empty()).count() {
+            0 => Tree::Empty,
+            1 => children.into_iter().find(|child| !child.is_empty()).unwrap(),
+            _ => {
+                let population = children.iter().map(|child| child.population(&self.nodes)).sum();
+                Tree::Node(self.nodes.alloc(SquareNode { area, population, children }))
+            }
+        }
+    }
+
+    /// Builds node holding both given disjoint trees, from the resulting arena
+    fn merge(&mut self, lhs: Tree<T, C>, rhs: Tree<T, C>) -> Tree<T, C> {
+        let (lhs_area, rhs_area) = match (area(&lhs, &self.nodes), area(&rhs, &self.nodes)) {
+            (Some(lhs_area), Some(rhs_area)) => (lhs_area, rhs_area),
+            (None, _) => return rhs,
+            (_, None) => return lhs,
+        };
+
+        let mut node = SquareNode::new(BinarySquare::common(&lhs_area, &rhs_area).unwrap());
+        node.population = lhs.population(&self.nodes) + rhs.population(&self.nodes);
+        node.children[node.area.quarter(&lhs_area.anchor) as usize] = lhs;
+        node.children[node.area.quarter(&rhs_area.anchor) as usize] = rhs;
+
+        Tree::Node(self.nodes.alloc(node))
+    }
+}
+
+/// Square covered by tree
+fn area<T, C: Coordinate>(tree: &Tree<T, C>, nodes: &Arena<T, C>) -> Option<BinarySquare<C>> {
+    match tree {
+        Tree::Empty => None,
+        Tree::Leaf(pt, _) => Some(BinarySquare::wrapping(*pt)),
+        &Tree::Node(id) => Some(nodes[id].area),
+    }
+}
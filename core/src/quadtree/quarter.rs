@@ -1,5 +1,6 @@
 use std::mem;
 use na::Point2;
+use crate::coordinate::Coordinate;
 
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -10,14 +11,14 @@ pub enum Quarter {
     SouthWest = 0b00,
 }
 
-pub fn global_quarter(point: &Point2<i32>) -> Quarter {
+pub fn global_quarter<C: Coordinate>(point: &Point2<C>) -> Quarter {
     let mut quarter = 0u8;
 
-    if point.x >= 0 {
+    if point.x >= C::ZERO {
         quarter |= 0b10;
     }
 
-    if point.y >= 0 {
+    if point.y >= C::ZERO {
         quarter |= 0b01;
     }
 
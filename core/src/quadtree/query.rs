@@ -0,0 +1,49 @@
+use na::Point2;
+use crate::coordinate::Coordinate;
+use py::{Holds, Overlaps};
+use crate::quadtree::arena::Arena;
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::node::Node;
+use crate::quadtree::tree::Tree;
+
+pub struct Query<'a, B: Holds<Point2<C>>, T, C: Coordinate = i32> {
+    bbox: B,
+    nodes: &'a Arena<T, C>,
+    stack: Vec<&'a Tree<T, C>>,
+}
+
+impl<'a, B: Clone + Holds<Point2<C>> + Overlaps<BinarySquare<C>>, T, C: Coordinate> Query<'a, B, T, C> {
+    #[inline]
+    pub fn new<N: Node<T, C>>(bbox: &B, root: &'a N, nodes: &'a Arena<T, C>) -> Query<'a, B, T, C> {
+        Query {
+            bbox: bbox.clone(),
+            nodes,
+            stack: root.children().collect(),
+        }
+    }
+}
+
+impl<'a, B: Holds<Point2<C>> + Overlaps<BinarySquare<C>>, T, C: Coordinate> Iterator for Query<'a, B, T, C> {
+    type Item = (&'a Point2<C>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop() {
+                None => return None,
+                Some(Tree::Empty) => (),
+                Some(Tree::Leaf(pt, value)) => {
+                    if self.bbox.holds(pt) {
+                        return Some((pt, value));
+                    }
+                },
+                Some(&Tree::Node(id)) => {
+                    let child = &self.nodes[id];
+
+                    if self.bbox.overlaps(&child.area) {
+                        self.stack.extend(&child.children)
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::slice::{Iter, IterMut};
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::quadtree::binary_square::BinarySquare;
+use crate::quadtree::node::Node;
+use crate::quadtree::tree::Tree;
+
+/// Quadtree node
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SquareNode<T, C: Coordinate = i32> {
+    pub area: BinarySquare<C>,
+    pub population: usize,
+    pub children: [Tree<T, C>; 4],
+}
+
+impl<T, C: Coordinate> SquareNode<T, C> {
+    /// Create a new empty node
+    #[inline]
+    pub fn new(area: BinarySquare<C>) -> SquareNode<T, C> {
+        SquareNode {
+            area,
+            population: 0,
+            children: [Tree::Empty, Tree::Empty, Tree::Empty, Tree::Empty],
+        }
+    }
+}
+
+impl<T, C: Coordinate> Node<T, C> for SquareNode<T, C> {
+    #[inline]
+    fn children(&self) -> Iter<'_, Tree<T, C>> {
+        self.children.iter()
+    }
+
+    #[inline]
+    fn children_mut(&mut self) -> IterMut<'_, Tree<T, C>> {
+        self.children.iter_mut()
+    }
+
+    #[inline]
+    fn population(&self) -> usize {
+        self.population
+    }
+
+    #[inline]
+    fn population_mut(&mut self) -> &mut usize {
+        &mut self.population
+    }
+
+    #[inline]
+    fn child_holding(&self, point: &Point2<C>) -> &Tree<T, C> {
+        let idx = self.area.quarter(point) as usize;
+        unsafe { self.children.get_unchecked(idx) }
+    }
+
+    #[inline]
+    fn child_holding_mut(&mut self, point: &Point2<C>) -> &mut Tree<T, C> {
+        let idx = self.area.quarter(point) as usize;
+        unsafe { self.children.get_unchecked_mut(idx) }
+    }
+}
@@ -0,0 +1,44 @@
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::quadtree::arena::{Arena, NodeId};
+
+/// Quadtree itself, nodes being stored in an arena
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Tree<T, C: Coordinate = i32> {
+    Leaf(Point2<C>, T),
+    Node(NodeId),
+    Empty
+}
+
+impl<T, C: Coordinate> Tree<T, C> {
+    /// Number of points held by tree
+    #[inline]
+    pub fn population(&self, nodes: &Arena<T, C>) -> usize {
+        match self {
+            Tree::Leaf(..) => 1,
+            Tree::Node(id) => nodes[*id].population,
+            Tree::Empty => 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Tree::Empty)
+    }
+}
+
+// Utils
+/// Test if both trees hold the same structure and values, each one using its own arena
+pub fn same_structure<T: PartialEq, C: Coordinate>(lhs: &Tree<T, C>, lhs_nodes: &Arena<T, C>, rhs: &Tree<T, C>, rhs_nodes: &Arena<T, C>) -> bool {
+    match (lhs, rhs) {
+        (&Tree::Node(lhs), &Tree::Node(rhs)) => {
+            let (lhs, rhs) = (&lhs_nodes[lhs], &rhs_nodes[rhs]);
+
+            lhs.area == rhs.area
+                && lhs.population == rhs.population
+                && lhs.children.iter().zip(&rhs.children)
+                    .all(|(lhs, rhs)| same_structure(lhs, lhs_nodes, rhs, rhs_nodes))
+        },
+        _ => lhs == rhs,
+    }
+}
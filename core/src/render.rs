@@ -0,0 +1,106 @@
+pub mod png;
+
+/// Greatest number of pixels of rendered images
+pub const MAX_PIXELS: u64 = 1 << 26;
+
+/// Color, as red, green and blue components
+pub type Rgb = [u8; 3];
+
+/// Colors of cell states, used to render universes
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Palette {
+    pub alive: Rgb,
+    pub dead: Rgb,
+    pub dying: Vec<Rgb>,
+}
+
+// Methods
+impl Palette {
+    /// Colors of the light universe
+    pub fn light() -> Palette {
+        Palette {
+            alive: [0x00, 0x00, 0x00],
+            dead: [0xff, 0xff, 0xff],
+            dying: vec![[0x55, 0x55, 0x55], [0xaa, 0xaa, 0xaa]],
+        }
+    }
+
+    /// Colors of the dark universe
+    pub fn dark() -> Palette {
+        Palette {
+            alive: [0xff, 0xff, 0xff],
+            dead: [0x00, 0x00, 0x00],
+            dying: vec![[0xaa, 0xaa, 0xaa], [0x55, 0x55, 0x55]],
+        }
+    }
+
+    /// Color of cells in given state, the last dying color being used for all following states
+    pub fn state_color(&self, state: u8) -> Rgb {
+        match state {
+            0 => self.dead,
+            1 => self.alive,
+            _ => self.dying.get(state as usize - 2)
+                .or(self.dying.last())
+                .copied()
+                .unwrap_or(self.alive),
+        }
+    }
+}
+
+// Utils
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::light()
+    }
+}
+
+/// Parses a css hexadecimal color (#rgb or #rrggbb)
+pub fn parse_color(color: &str) -> Result<Rgb, &'static str> {
+    let error = "colors must be formed as #rgb or #rrggbb";
+    let digits = color.strip_prefix('#').ok_or(error)?;
+
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(error);
+    }
+
+    let component = |hex: &str| u8::from_str_radix(hex, 16).map_err(|_| error);
+
+    match digits.len() {
+        3 => {
+            let mut rgb = [0; 3];
+
+            for (value, idx) in rgb.iter_mut().zip(0..3) {
+                *value = component(&digits[idx..idx + 1])? * 0x11;
+            }
+
+            Ok(rgb)
+        },
+        6 => Ok([component(&digits[0..2])?, component(&digits[2..4])?, component(&digits[4..6])?]),
+        _ => Err(error),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_color() {
+        let palette = Palette::light();
+
+        assert_eq!(palette.state_color(0), [0xff, 0xff, 0xff]);
+        assert_eq!(palette.state_color(1), [0x00, 0x00, 0x00]);
+        assert_eq!(palette.state_color(3), [0xaa, 0xaa, 0xaa]);
+        assert_eq!(palette.state_color(10), [0xaa, 0xaa, 0xaa]);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#1a2B3c"), Ok([0x1a, 0x2b, 0x3c]));
+        assert_eq!(parse_color("#f80"), Ok([0xff, 0x88, 0x00]));
+        assert_eq!(parse_color("red"), Err("colors must be formed as #rgb or #rrggbb"));
+        assert_eq!(parse_color("#12345"), Err("colors must be formed as #rgb or #rrggbb"));
+        assert_eq!(parse_color("#+1+2+3"), Err("colors must be formed as #rgb or #rrggbb"));
+    }
+}
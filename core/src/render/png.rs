@@ -0,0 +1,205 @@
+use std::iter;
+use crate::render::Rgb;
+use crate::utils::crc32;
+
+/// Bytes starting every png file
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Deflate window size, limiting match distances
+const WINDOW_SIZE: usize = 32768;
+
+/// Deflate match lengths limits
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// Deflate length codes (from 257): base length and extra bits
+const LENGTH_CODES: [(u16, u8); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0), (11, 1), (13, 1), (15, 1), (17, 1), (19, 2), (23, 2), (27, 2),
+    (31, 2), (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4), (115, 4), (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+
+/// Deflate distance codes: base distance and extra bits
+const DISTANCE_CODES: [(u16, u8); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2), (17, 3), (25, 3), (33, 4), (49, 4), (65, 5), (97, 5), (129, 6),
+    (193, 6), (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9), (2049, 10), (3073, 10), (4097, 11), (6145, 11),
+    (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+
+/// Encodes an indexed color image as png. Pixels are palette indexes, row by row.
+pub fn encode(width: u32, height: u32, palette: &[Rgb], pixels: &[u8]) -> Vec<u8> {
+    let mut output = Vec::from(SIGNATURE);
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    header.extend([8, 3, 0, 0, 0]); // 8 bits depth, indexed colors, no interlace
+
+    write_chunk(&mut output, b"IHDR", &header);
+    write_chunk(&mut output, b"PLTE", palette.concat().as_slice());
+
+    // Each row starts with its filter type (0 meaning none)
+    let data = pixels.chunks(width as usize)
+        .flat_map(|row| iter::once(&0).chain(row))
+        .copied()
+        .collect::<Vec<_>>();
+
+    write_chunk(&mut output, b"IDAT", &zlib(&data, width as usize + 1));
+    write_chunk(&mut output, b"IEND", &[]);
+
+    output
+}
+
+// Utils
+/// Writes chunk: length, type, data and checksum of type and data
+fn write_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend((data.len() as u32).to_be_bytes());
+
+    let start = output.len();
+    output.extend(kind);
+    output.extend(data);
+
+    let checksum = crc32(&output[start..]);
+    output.extend(checksum.to_be_bytes());
+}
+
+/// Compresses data in zlib format, using a single deflate block with fixed huffman codes.
+/// Matches are only searched in the previous byte and the previous row, which suits rendered cells well.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(0b011, 3); // last block, fixed huffman codes
+
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let best = [1, stride].into_iter()
+            .filter(|&distance| distance <= pos && distance <= WINDOW_SIZE)
+            .map(|distance| (match_length(data, pos, distance), distance))
+            .max_by_key(|&(length, _)| length)
+            .filter(|&(length, _)| length >= MIN_MATCH);
+
+        match best {
+            Some((length, distance)) => {
+                bits.write_length(length);
+                bits.write_distance(distance);
+                pos += length;
+            },
+            None => {
+                bits.write_literal(data[pos] as u16);
+                pos += 1;
+            },
+        }
+    }
+
+    bits.write_literal(256); // end of block
+
+    let mut output = vec![0x78, 0x01];
+    output.extend(bits.finish());
+    output.extend(adler32(data).to_be_bytes());
+
+    output
+}
+
+/// Length of data matching at given distance before position
+fn match_length(data: &[u8], pos: usize, distance: usize) -> usize {
+    data[pos..].iter()
+        .zip(&data[pos - distance..])
+        .take(MAX_MATCH)
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Adler-32 checksum, ending zlib streams
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    (b << 16) | a
+}
+
+/// Writes deflate bit stream, least significant bits first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes huffman code, which are stored most significant bit first
+    fn write_code(&mut self, code: u32, count: u32) {
+        self.write(code.reverse_bits() >> (32 - count), count);
+    }
+
+    /// Writes literal/length symbol, using fixed huffman codes
+    fn write_literal(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_length(&mut self, length: usize) {
+        let code = LENGTH_CODES.iter().rposition(|&(base, _)| base as usize <= length).unwrap();
+        let (base, extra) = LENGTH_CODES[code];
+
+        self.write_literal(257 + code as u16);
+        self.write((length - base as usize) as u32, extra as u32);
+    }
+
+    fn write_distance(&mut self, distance: usize) {
+        let code = DISTANCE_CODES.iter().rposition(|&(base, _)| base as usize <= distance).unwrap();
+        let (base, extra) = DISTANCE_CODES[code];
+
+        self.write_code(code as u32, 5);
+        self.write((distance - base as usize) as u32, extra as u32);
+    }
+
+    /// Pads last byte and returns written bytes
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+
+        self.bytes
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn test_encode() {
+        let png = encode(2, 2, &[[0xff, 0xff, 0xff], [0x00, 0x00, 0x00]], &[0, 1, 1, 0]);
+
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(png[8..33], [
+            0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0, 2, 0, 0, 0, 2, 8, 3, 0, 0, 0,
+            0x45, 0x68, 0xfd, 0x16,
+        ]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+}
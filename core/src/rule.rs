@@ -0,0 +1,565 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use crate::neighborhood::{Neighborhood, NeighborhoodShape, Neighbors};
+use crate::rule::hensel::Configurations;
+
+mod hensel;
+mod map;
+
+/// State of dead cells
+pub const DEAD: u8 = 0;
+
+/// State of alive cells, greater states are dying cells
+pub const ALIVE: u8 = 1;
+
+/// Maximum range of larger than life neighborhoods
+pub const MAX_RANGE: i32 = 500;
+
+/// Life-like rule (B/S notation), with optional dying states (Generations rules),
+/// extended neighborhoods (Larger than Life rules) or non-totalistic counts (Hensel notation and MAP rules)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rule {
+    birth: Counts,
+    survival: Counts,
+    states: u8,
+    neighborhood: Neighborhood,
+    middle: bool,
+}
+
+/// Set of neighbor counts
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Counts {
+    /// Bit mask of counts, for classic 8 cells neighborhood
+    Mask(u16),
+    /// Inclusive interval of counts, for larger than life rules
+    Interval(usize, usize),
+    /// Set of neighbor configurations, for non-totalistic rules
+    Configurations(Configurations),
+}
+
+impl Rule {
+    /// Conway's game of life rule (B3/S23)
+    pub fn conway() -> Rule {
+        Rule {
+            birth: Counts::Mask(0b1000),
+            survival: Counts::Mask(0b1100),
+            states: 2,
+            neighborhood: Neighborhood::moore(),
+            middle: false,
+        }
+    }
+
+    /// Builds a rule from the neighbor counts causing birth and survival
+    pub fn new(birth: &[usize], survival: &[usize]) -> Result<Rule, &'static str> {
+        Rule::generations(birth, survival, 2)
+    }
+
+    /// Builds a generations rule: cells failing to survive pass through dying states before disappearing
+    pub fn generations(birth: &[usize], survival: &[usize], states: u8) -> Result<Rule, &'static str> {
+        if states < 2 {
+            return Err("number of states must be an integer between 2 and 255");
+        }
+
+        Ok(Rule {
+            birth: Counts::Mask(counts_mask(birth.iter().copied())?),
+            survival: Counts::Mask(counts_mask(survival.iter().copied())?),
+            states,
+            neighborhood: Neighborhood::moore(),
+            middle: false,
+        })
+    }
+
+    /// Builds a larger than life rule, counting alive cells in given neighborhood.
+    /// If middle is true, center cell is counted along with its neighbors.
+    pub fn larger_than_life(
+        neighborhood: Neighborhood,
+        middle: bool,
+        birth: RangeInclusive<usize>,
+        survival: RangeInclusive<usize>,
+        states: u8,
+    ) -> Result<Rule, &'static str> {
+        if !(1..=MAX_RANGE).contains(&neighborhood.range()) {
+            return Err("range must be an integer between 1 and 500");
+        }
+
+        if states < 2 {
+            return Err("number of states must be an integer between 2 and 255");
+        }
+
+        let max = neighborhood.size() + middle as usize;
+
+        if *birth.end() > max || *survival.end() > max {
+            return Err("neighbor counts must not exceed neighborhood size");
+        }
+
+        if birth.contains(&0) {
+            return Err("rules with birth on 0 neighbors (B0) are not supported");
+        }
+
+        Ok(Rule {
+            birth: Counts::Interval(*birth.start(), *birth.end()),
+            survival: Counts::Interval(*survival.start(), *survival.end()),
+            states,
+            neighborhood,
+            middle,
+        })
+    }
+
+    /// Number of cell states, including dead and alive states
+    #[inline]
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Cells counted as neighbors
+    #[inline]
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Computes next state of a cell, according to its current state and alive neighbors (center excluded).
+    /// Non-totalistic rules need the neighbors configuration.
+    #[inline]
+    pub fn evolve(&self, is_alive: bool, neighbors: Neighbors) -> bool {
+        if !is_alive {
+            self.birth.contains(neighbors)
+        } else if self.middle {
+            self.survival.contains(Neighbors::Count(neighbors.count() + 1))
+        } else {
+            self.survival.contains(neighbors)
+        }
+    }
+
+    /// Computes next state of a cell (0 for dead, 1 for alive, others for dying),
+    /// according to its current state and alive neighbors
+    pub fn next_state(&self, state: u8, neighbors: Neighbors) -> u8 {
+        match state {
+            DEAD => self.evolve(false, neighbors) as u8,
+            ALIVE if self.evolve(true, neighbors) => ALIVE,
+            _ if state + 1 < self.states => state + 1,
+            _ => DEAD,
+        }
+    }
+}
+
+impl Counts {
+    /// Test if set does not distinguish rotated or reflected neighbor configurations
+    fn is_isotropic(&self) -> bool {
+        match self {
+            Counts::Configurations(configurations) => hensel::is_isotropic(configurations),
+            _ => true,
+        }
+    }
+
+    /// Returns all neighbor configurations matching the set
+    fn configurations(&self) -> Configurations {
+        let mut configurations = [0; 4];
+
+        for configuration in 0..=255 {
+            if self.contains(Neighbors::Configuration(configuration)) {
+                hensel::insert(&mut configurations, configuration);
+            }
+        }
+
+        configurations
+    }
+
+    /// Test if given neighbors match the set
+    #[inline]
+    fn contains(&self, neighbors: Neighbors) -> bool {
+        match (self, neighbors) {
+            (Counts::Configurations(configurations), Neighbors::Configuration(configuration)) => {
+                hensel::contains(configurations, configuration)
+            },
+            (Counts::Configurations(_), Neighbors::Count(_)) => false,
+            (Counts::Mask(mask), neighbors) => neighbors.count() < 9 && mask & (1 << neighbors.count()) != 0,
+            (&Counts::Interval(min, max), neighbors) => (min..=max).contains(&neighbors.count()),
+        }
+    }
+}
+
+// Utils
+impl From<Configurations> for Counts {
+    /// Builds a set from neighbor configurations, simplified to a count mask if possible
+    fn from(configurations: Configurations) -> Self {
+        match hensel::counts_mask(&configurations) {
+            Some(mask) => Counts::Mask(mask),
+            None => Counts::Configurations(configurations),
+        }
+    }
+}
+
+fn counts_mask(counts: impl Iterator<Item = usize>) -> Result<u16, &'static str> {
+    let mut mask = 0;
+
+    for count in counts {
+        if count > 8 {
+            return Err("neighbor counts must be between 0 and 8");
+        }
+
+        mask |= 1 << count;
+    }
+
+    Ok(mask)
+}
+
+fn parse_counts(counts: &str) -> Result<Counts, &'static str> {
+    if counts.contains(|c: char| c.is_ascii_alphabetic() || c == '-') {
+        return hensel::parse(counts).map(Counts::from);
+    }
+
+    let counts = counts.chars()
+        .map(|c| c.to_digit(10).map(|d| d as usize))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("neighbor counts must be digits")?;
+
+    counts_mask(counts.into_iter()).map(Counts::Mask)
+}
+
+fn parse_states(states: Option<&str>) -> Result<u8, &'static str> {
+    match states {
+        None => Ok(2),
+        Some(states) => states.trim_start_matches(['C', 'c', 'G', 'g']).parse::<u8>()
+            .ok()
+            .filter(|&states| states >= 2)
+            .ok_or("number of states must be an integer between 2 and 255"),
+    }
+}
+
+fn parse_interval(interval: &str) -> Result<RangeInclusive<usize>, &'static str> {
+    let (min, max) = interval.split_once("..")
+        .ok_or("neighbor count intervals must be 2 integers separated by '..'")?;
+
+    let min = min.parse().map_err(|_| "neighbor count intervals must be 2 integers separated by '..'")?;
+    let max = max.parse().map_err(|_| "neighbor count intervals must be 2 integers separated by '..'")?;
+
+    if min > max {
+        return Err("neighbor count intervals must not be reversed");
+    }
+
+    Ok(min..=max)
+}
+
+/// Parses a larger than life rulestring (like R5,C0,M1,S34..58,B34..45,NM)
+fn parse_larger_than_life(s: &str) -> Result<Rule, &'static str> {
+    let mut range = None;
+    let mut states = 2;
+    let mut middle = false;
+    let mut birth = None;
+    let mut survival = None;
+    let mut shape = NeighborhoodShape::Moore;
+
+    for part in s.split(',') {
+        let kind = part.chars().next().unwrap_or_default();
+        let value = &part[kind.len_utf8()..];
+
+        match kind.to_ascii_uppercase() {
+            'R' => range = Some(value.parse().map_err(|_| "range must be an integer between 1 and 500")?),
+            'C' => {
+                states = value.parse::<u8>()
+                    .map_err(|_| "number of states must be an integer between 2 and 255")?
+                    .max(2);
+            },
+            'M' => {
+                middle = match value {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err("middle must be 0 or 1"),
+                }
+            },
+            'S' => survival = Some(parse_interval(value)?),
+            'B' => birth = Some(parse_interval(value)?),
+            'N' => {
+                shape = match value {
+                    "M" | "m" => NeighborhoodShape::Moore,
+                    "N" | "n" => NeighborhoodShape::VonNeumann,
+                    "C" | "c" => NeighborhoodShape::Circular,
+                    _ => return Err("unknown neighborhood"),
+                }
+            },
+            _ => return Err("unknown larger than life rule part"),
+        }
+    }
+
+    Rule::larger_than_life(
+        Neighborhood::new(shape, range.ok_or("larger than life rule must define a range")?),
+        middle,
+        birth.ok_or("larger than life rule must define birth counts")?,
+        survival.ok_or("larger than life rule must define survival counts")?,
+        states,
+    )
+}
+
+fn write_counts(f: &mut fmt::Formatter<'_>, counts: Counts) -> fmt::Result {
+    match counts {
+        Counts::Mask(mask) => {
+            for count in 0..9 {
+                if mask & (1 << count) != 0 {
+                    write!(f, "{count}")?;
+                }
+            }
+
+            Ok(())
+        },
+        Counts::Interval(min, max) => write!(f, "{min}..{max}"),
+        Counts::Configurations(configurations) => hensel::write(f, &configurations),
+    }
+}
+
+impl Default for Rule {
+    #[inline]
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = &'static str;
+
+    /// Parses B/S notation (B3/S23, or B2-a/S12 using hensel notation), legacy notation (23/3),
+    /// MAP rules, and larger than life notation (R5,C0,M1,S34..58,B34..45,NM)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.starts_with(['R', 'r']) {
+            return parse_larger_than_life(s);
+        }
+
+        if let Some(map) = s.strip_prefix("MAP") {
+            let (map, states) = match map.split_once('/') {
+                Some((map, states)) => (map, Some(states)),
+                None => (map, None),
+            };
+
+            let (birth, survival) = map::parse(map)?;
+
+            if hensel::contains(&birth, 0) {
+                return Err("rules with birth on 0 neighbors (B0) are not supported");
+            }
+
+            return Ok(Rule {
+                birth: Counts::from(birth),
+                survival: Counts::from(survival),
+                states: parse_states(states)?,
+                neighborhood: Neighborhood::moore(),
+                middle: false,
+            });
+        }
+
+        let parts = s.split('/').collect::<Vec<_>>();
+
+        let (lhs, rhs, states) = match parts[..] {
+            [lhs, rhs] => (lhs, rhs, None),
+            [lhs, rhs, states] => (lhs, rhs, Some(states)),
+            _ => return Err("rule must contain 2 or 3 parts separated by '/'"),
+        };
+
+        let (birth, survival) = match (lhs.chars().next(), rhs.chars().next()) {
+            (Some('B' | 'b'), Some('S' | 's')) => (parse_counts(&lhs[1..])?, parse_counts(&rhs[1..])?),
+            (Some('S' | 's'), Some('B' | 'b')) => (parse_counts(&rhs[1..])?, parse_counts(&lhs[1..])?),
+            _ => (parse_counts(rhs)?, parse_counts(lhs)?), // Legacy S/B form
+        };
+
+        if birth.contains(Neighbors::Configuration(0)) {
+            return Err("rules with birth on 0 neighbors (B0) are not supported");
+        }
+
+        Ok(Rule {
+            birth,
+            survival,
+            states: parse_states(states)?,
+            neighborhood: Neighborhood::moore(),
+            middle: false,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (Counts::Interval(..), Counts::Interval(..)) = (self.birth, self.survival) {
+            let shape = match self.neighborhood.shape() {
+                NeighborhoodShape::Moore => 'M',
+                NeighborhoodShape::VonNeumann => 'N',
+                NeighborhoodShape::Circular => 'C',
+            };
+
+            let states = if self.states > 2 { self.states } else { 0 };
+
+            write!(f, "R{},C{states},M{},S", self.neighborhood.range(), self.middle as u8)?;
+            write_counts(f, self.survival)?;
+            write!(f, ",B")?;
+            write_counts(f, self.birth)?;
+
+            return write!(f, ",N{shape}");
+        }
+
+        if !self.birth.is_isotropic() || !self.survival.is_isotropic() {
+            write!(f, "MAP")?;
+            map::write(f, &self.birth.configurations(), &self.survival.configurations())?;
+
+            if self.states > 2 {
+                write!(f, "/C{}", self.states)?;
+            }
+
+            return Ok(());
+        }
+
+        write!(f, "B")?;
+        write_counts(f, self.birth)?;
+        write!(f, "/S")?;
+        write_counts(f, self.survival)?;
+
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bs_notation() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::conway()));
+        assert_eq!("b3/s23".parse(), Ok(Rule::conway()));
+        assert_eq!("S23/B3".parse(), Ok(Rule::conway()));
+        assert_eq!("B36/S23".parse(), Rule::new(&[3, 6], &[2, 3]));
+        assert_eq!("B3678/S34678".parse(), Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8]));
+        assert_eq!("B2/S".parse(), Rule::new(&[2], &[]));
+    }
+
+    #[test]
+    fn test_parse_legacy_notation() {
+        assert_eq!("23/3".parse(), Ok(Rule::conway()));
+        assert_eq!("23/36".parse(), Rule::new(&[3, 6], &[2, 3]));
+        assert_eq!("/2".parse(), Rule::new(&[2], &[]));
+    }
+
+    #[test]
+    fn test_parse_generations() {
+        assert_eq!("B2/S/C3".parse(), Rule::generations(&[2], &[], 3));
+        assert_eq!("/2/3".parse(), Rule::generations(&[2], &[], 3));
+        assert_eq!("345/2/4".parse(), Rule::generations(&[2], &[3, 4, 5], 4));
+        assert_eq!("B3/S23/G2".parse(), Ok(Rule::conway()));
+    }
+
+    #[test]
+    fn test_parse_larger_than_life() {
+        let bosco = Rule::larger_than_life(Neighborhood::new(NeighborhoodShape::Moore, 5), true, 34..=45, 34..=58, 2);
+
+        assert_eq!("R5,C0,M1,S34..58,B34..45,NM".parse(), bosco);
+        assert_eq!("r5,c0,m1,s34..58,b34..45,nm".parse(), bosco);
+        assert_eq!(
+            "R2,C3,M0,S2..5,B3..4,NN".parse(),
+            Rule::larger_than_life(Neighborhood::new(NeighborhoodShape::VonNeumann, 2), false, 3..=4, 2..=5, 3)
+        );
+        assert_eq!("R3,S4..8,B5..6,NC".parse::<Rule>().unwrap().neighborhood(), Neighborhood::new(NeighborhoodShape::Circular, 3));
+
+        assert_eq!("R0,C0,M0,S1..1,B1..1,NM".parse::<Rule>(), Err("range must be an integer between 1 and 500"));
+        assert_eq!("R1,C0,M0,S1..9,B1..1,NM".parse::<Rule>(), Err("neighbor counts must not exceed neighborhood size"));
+        assert!("R1,C0,M1,S1..9,B1..1,NM".parse::<Rule>().is_ok());
+        assert_eq!("R2,C0,M0,S1..2,B0..2,NM".parse::<Rule>(), Err("rules with birth on 0 neighbors (B0) are not supported"));
+        assert_eq!("R2,C0,M0,S1-2,B1..2,NM".parse::<Rule>(), Err("neighbor count intervals must be 2 integers separated by '..'"));
+        assert_eq!("R2,C0,M0,S58..34,B1..2,NM".parse::<Rule>(), Err("neighbor count intervals must not be reversed"));
+        assert_eq!("R2,C0,M0,B1..2,NM".parse::<Rule>(), Err("larger than life rule must define survival counts"));
+        assert_eq!("R2,C0,M0,S1..2,B1..2,NX".parse::<Rule>(), Err("unknown neighborhood"));
+    }
+
+    #[test]
+    fn test_parse_hensel_notation() {
+        let rule = "B2-a/S12".parse::<Rule>().unwrap();
+
+        assert_eq!("b2ceikn/s1ce2".parse(), Ok(rule));
+        assert_eq!("12/2-a".parse(), Ok(rule));
+        assert_ne!(rule, Rule::new(&[2], &[1, 2]).unwrap());
+
+        assert_eq!("B2x/S23".parse::<Rule>(), Err("invalid hensel letter for neighbor count"));
+        assert_eq!("B3/S2-".parse::<Rule>(), Err("'-' must be followed by hensel letters"));
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let conway = "MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA";
+        assert_eq!(conway.parse(), Ok(Rule::conway()));
+
+        assert_eq!("MAPARYX".parse::<Rule>(), Err("MAP rules must contain 512 base64 encoded bits"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("B3S23".parse::<Rule>(), Err("rule must contain 2 or 3 parts separated by '/'"));
+        assert_eq!("B3/S23/C3/C4".parse::<Rule>(), Err("rule must contain 2 or 3 parts separated by '/'"));
+        assert_eq!("B2/S/C1".parse::<Rule>(), Err("number of states must be an integer between 2 and 255"));
+        assert_eq!("B2/S/C256".parse::<Rule>(), Err("number of states must be an integer between 2 and 255"));
+        assert_eq!("B3/S29".parse::<Rule>(), Err("neighbor counts must be between 0 and 8"));
+        assert_eq!("B3/Sx".parse::<Rule>(), Err("neighbor counts must be digits"));
+        assert_eq!("B03/S23".parse::<Rule>(), Err("rules with birth on 0 neighbors (B0) are not supported"));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Rule::conway().to_string(), "B3/S23");
+        assert_eq!("34678/3678".parse::<Rule>().unwrap().to_string(), "B3678/S34678");
+        assert_eq!("B2/S".parse::<Rule>().unwrap().to_string(), "B2/S");
+        assert_eq!("345/2/4".parse::<Rule>().unwrap().to_string(), "B2/S345/C4");
+        assert_eq!("R5,C0,M1,S34..58,B34..45,NM".parse::<Rule>().unwrap().to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!("R2,C3,S2..5,B3..4,NC".parse::<Rule>().unwrap().to_string(), "R2,C3,M0,S2..5,B3..4,NC");
+        assert_eq!("B2ceikn/S1ce2".parse::<Rule>().unwrap().to_string(), "B2-a/S12");
+        assert_eq!("B3/S2-i34q".parse::<Rule>().unwrap().to_string(), "B3/S2-i34q");
+        assert_eq!("B2c3eai/S1e/C4".parse::<Rule>().unwrap().to_string(), "B2c3eai/S1e/C4");
+
+        // Isotropic MAP rules are written in hensel notation, others keep their MAP table
+        let map = "MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA";
+        assert_eq!(map.parse::<Rule>().unwrap().to_string(), "B3/S23");
+
+        let map = "MAPQRYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA";
+        assert_eq!(map.parse::<Rule>().unwrap().to_string(), map);
+    }
+
+    #[test]
+    fn test_evolve() {
+        let rule = Rule::conway();
+
+        assert!(rule.evolve(false, Neighbors::Count(3)));
+        assert!(!rule.evolve(false, Neighbors::Count(2)));
+        assert!(rule.evolve(true, Neighbors::Count(2)));
+        assert!(rule.evolve(true, Neighbors::Configuration(0b0001_1000)));
+        assert!(rule.evolve(true, Neighbors::Count(3)));
+        assert!(!rule.evolve(true, Neighbors::Count(4)));
+        assert!(!rule.evolve(true, Neighbors::Count(9)));
+
+        let rule = Rule::larger_than_life(Neighborhood::new(NeighborhoodShape::Moore, 2), true, 3..=4, 3..=4, 2).unwrap();
+
+        assert!(rule.evolve(false, Neighbors::Count(3)));
+        assert!(rule.evolve(true, Neighbors::Count(2)));
+        assert!(!rule.evolve(true, Neighbors::Count(4)));
+
+        // 2 adjacent neighbors (2a) do not cause birth
+        let rule = "B2-a/S12".parse::<Rule>().unwrap();
+
+        assert!(rule.evolve(false, Neighbors::Configuration(0b0001_1000)));
+        assert!(!rule.evolve(false, Neighbors::Configuration(0b1100_0000)));
+        assert!(!rule.evolve(false, Neighbors::Configuration(0b0000_0011)));
+        assert!(rule.evolve(true, Neighbors::Configuration(0b0000_0011)));
+    }
+
+    #[test]
+    fn test_next_state() {
+        let brain = Rule::generations(&[2], &[], 3).unwrap();
+
+        assert_eq!(brain.next_state(0, Neighbors::Count(2)), 1);
+        assert_eq!(brain.next_state(0, Neighbors::Count(3)), 0);
+        assert_eq!(brain.next_state(1, Neighbors::Count(2)), 2);
+        assert_eq!(brain.next_state(2, Neighbors::Count(2)), 0);
+
+        let conway = Rule::conway();
+
+        assert_eq!(conway.next_state(1, Neighbors::Count(2)), 1);
+        assert_eq!(conway.next_state(1, Neighbors::Count(4)), 0);
+    }
+}
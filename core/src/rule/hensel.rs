@@ -0,0 +1,202 @@
+use std::fmt;
+use crate::neighborhood::symmetries;
+
+/// Set of neighbor configurations, one bit per configuration
+pub type Configurations = [u64; 4];
+
+/// Hensel notation letters for each neighbor count up to 4, with a configuration representing each one.
+/// Configurations for counts above 4 are complements of those for 8 - count.
+const LETTERS: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', 0b0000_0001), ('e', 0b0000_0010)],
+    &[
+        ('c', 0b0000_0101), ('e', 0b0000_1010), ('a', 0b0000_0011),
+        ('i', 0b0001_1000), ('k', 0b0001_0001), ('n', 0b0010_0100),
+    ],
+    &[
+        ('c', 0b0010_0101), ('e', 0b0001_1010), ('a', 0b0000_1011), ('i', 0b0000_0111), ('k', 0b0011_0010),
+        ('n', 0b0000_1101), ('j', 0b0000_1110), ('q', 0b0010_0110), ('r', 0b0001_1001), ('y', 0b0011_0001),
+    ],
+    &[
+        ('c', 0b1010_0101), ('e', 0b0101_1010), ('a', 0b0000_1111), ('i', 0b0001_1101), ('k', 0b0011_0011),
+        ('n', 0b0010_0111), ('j', 0b0011_1010), ('q', 0b0011_0110), ('r', 0b0001_1011), ('y', 0b0011_0101),
+        ('t', 0b0011_1001), ('w', 0b0010_1110), ('z', 0b0011_1100),
+    ],
+];
+
+/// Test if set holds given configuration
+#[inline]
+pub fn contains(configurations: &Configurations, configuration: u8) -> bool {
+    configurations[configuration as usize / 64] & (1 << (configuration % 64)) != 0
+}
+
+/// Adds given configuration to set
+#[inline]
+pub fn insert(configurations: &mut Configurations, configuration: u8) {
+    configurations[configuration as usize / 64] |= 1 << (configuration % 64);
+}
+
+/// Test if set does not distinguish rotated or reflected configurations
+pub fn is_isotropic(configurations: &Configurations) -> bool {
+    (0..=255).all(|configuration| {
+        let expected = contains(configurations, configuration);
+        symmetries(configuration).iter().all(|&sym| contains(configurations, sym) == expected)
+    })
+}
+
+/// Returns matching count mask, if set only depends on neighbor counts
+pub fn counts_mask(configurations: &Configurations) -> Option<u16> {
+    let mut mask = 0;
+
+    for count in 0..=8 {
+        let mut configurations = (0..=255u8)
+            .filter(|c| c.count_ones() == count)
+            .map(|c| contains(configurations, c));
+
+        let first = configurations.next().unwrap_or_default();
+
+        if !configurations.all(|included| included == first) {
+            return None;
+        }
+
+        mask |= (first as u16) << count;
+    }
+
+    Some(mask)
+}
+
+/// Parses neighbor counts in hensel notation (like 2-a3ij)
+pub fn parse(counts: &str) -> Result<Configurations, &'static str> {
+    let mut configurations = [0; 4];
+    let mut chars = counts.chars().peekable();
+
+    while let Some(digit) = chars.next() {
+        let count = digit.to_digit(10).ok_or("neighbor counts must be digits")? as usize;
+
+        if count > 8 {
+            return Err("neighbor counts must be between 0 and 8");
+        }
+
+        let negate = chars.next_if_eq(&'-').is_some();
+        let mut selected = String::new();
+
+        while let Some(letter) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+            selected.push(letter.to_ascii_lowercase());
+        }
+
+        if selected.is_empty() {
+            if negate {
+                return Err("'-' must be followed by hensel letters");
+            }
+
+            for configuration in (0..=255u8).filter(|c| c.count_ones() as usize == count) {
+                insert(&mut configurations, configuration);
+            }
+
+            continue;
+        }
+
+        let letters = letters(count).collect::<Vec<_>>();
+
+        if selected.chars().any(|letter| !letters.iter().any(|&(l, _)| l == letter)) {
+            return Err("invalid hensel letter for neighbor count");
+        }
+
+        for (letter, class) in letters {
+            if selected.contains(letter) != negate {
+                class.into_iter().for_each(|configuration| insert(&mut configurations, configuration));
+            }
+        }
+    }
+
+    Ok(configurations)
+}
+
+/// Writes isotropic configurations in hensel notation
+pub fn write(f: &mut fmt::Formatter<'_>, configurations: &Configurations) -> fmt::Result {
+    for count in 0..=8 {
+        if count % 8 == 0 {
+            if contains(configurations, if count == 0 { 0 } else { 0xff }) {
+                write!(f, "{count}")?;
+            }
+
+            continue;
+        }
+
+        let (present, missing): (Vec<_>, Vec<_>) = letters(count)
+            .partition(|(_, class)| contains(configurations, class[0]));
+
+        let present = present.into_iter().map(|(letter, _)| letter).collect::<String>();
+        let missing = missing.into_iter().map(|(letter, _)| letter).collect::<String>();
+
+        if missing.is_empty() {
+            write!(f, "{count}")?;
+        } else if present.len() > missing.len() {
+            write!(f, "{count}-{missing}")?;
+        } else if !present.is_empty() {
+            write!(f, "{count}{present}")?;
+        }
+    }
+
+    Ok(())
+}
+
+// Utils
+/// Iterates over letters of given neighbor count, along with configurations matching each one
+fn letters(count: usize) -> impl Iterator<Item = (char, [u8; 8])> {
+    let complement = count > 4;
+
+    LETTERS[if complement { 8 - count } else { count }].iter()
+        .map(move |&(letter, configuration)| {
+            (letter, symmetries(if complement { !configuration } else { configuration }))
+        })
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    #[test]
+    fn test_letters_partition_configurations() {
+        let mut configurations = [0; 4];
+
+        for count in 1..8 {
+            for (_, class) in letters(count) {
+                for configuration in class {
+                    assert_eq!(configuration.count_ones() as usize, count);
+                    insert(&mut configurations, configuration);
+                }
+            }
+
+            // Each configuration belongs to exactly one letter
+            let expected = (0..=255u8).filter(|c| c.count_ones() as usize == count).count();
+            let total = letters(count)
+                .map(|(_, class)| class.into_iter().collect::<HashSet<_>>().len())
+                .sum::<usize>();
+
+            assert_eq!(total, expected);
+        }
+
+        assert!(is_isotropic(&configurations));
+    }
+
+    #[test]
+    fn test_parse() {
+        let configurations = parse("2-a3").unwrap();
+
+        assert!(contains(&configurations, 0b0001_1000));
+        assert!(!contains(&configurations, 0b0000_0011));
+        assert!(!contains(&configurations, 0b1100_0000));
+        assert!(contains(&configurations, 0b0000_0111));
+        assert!(is_isotropic(&configurations));
+
+        assert_eq!(counts_mask(&configurations), None);
+        assert_eq!(counts_mask(&parse("2ceaikn3").unwrap()), Some(0b1100));
+
+        assert_eq!(parse("3-"), Err("'-' must be followed by hensel letters"));
+        assert_eq!(parse("1a"), Err("invalid hensel letter for neighbor count"));
+        assert_eq!(parse("9c"), Err("neighbor counts must be between 0 and 8"));
+    }
+}
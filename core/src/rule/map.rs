@@ -0,0 +1,77 @@
+use std::fmt;
+use crate::rule::hensel::{self, Configurations};
+
+/// Base64 alphabet used by MAP rules
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Number of base64 characters needed to encode the 512 bits table
+const LENGTH: usize = 512usize.div_ceil(6);
+
+/// Parses a MAP table (base64 encoded next state of all 512 configurations of the 3x3 square),
+/// returning birth and survival configurations
+pub fn parse(map: &str) -> Result<(Configurations, Configurations), &'static str> {
+    let digits = map.trim_end_matches('=').bytes()
+        .map(|c| ALPHABET.iter().position(|&a| a == c).map(|d| d as u32))
+        .collect::<Option<Vec<_>>>()
+        .filter(|digits| digits.len() == LENGTH)
+        .ok_or("MAP rules must contain 512 base64 encoded bits")?;
+
+    let mut birth = [0; 4];
+    let mut survival = [0; 4];
+
+    for index in 0..512 {
+        if digits[index / 6] & (1 << (5 - index % 6)) == 0 {
+            continue;
+        }
+
+        let (center, configuration) = split_index(index);
+
+        if center {
+            hensel::insert(&mut survival, configuration);
+        } else {
+            hensel::insert(&mut birth, configuration);
+        }
+    }
+
+    Ok((birth, survival))
+}
+
+/// Writes a MAP table from birth and survival configurations
+pub fn write(f: &mut fmt::Formatter<'_>, birth: &Configurations, survival: &Configurations) -> fmt::Result {
+    let mut digits = [0; LENGTH];
+
+    for index in 0..512 {
+        let (center, configuration) = split_index(index);
+        let table = if center { survival } else { birth };
+
+        if hensel::contains(table, configuration) {
+            digits[index / 6] |= 1 << (5 - index % 6);
+        }
+    }
+
+    for digit in digits {
+        write!(f, "{}", ALPHABET[digit] as char)?;
+    }
+
+    Ok(())
+}
+
+// Utils
+/// Splits MAP index (cells ordered NW, N, NE, W, center, E, SW, S, SE from highest to lowest bit)
+/// into center state and neighbor configuration
+#[inline]
+fn split_index(index: usize) -> (bool, u8) {
+    (index & 0b1_0000 != 0, (((index >> 5) << 4) | (index & 0b1111)) as u8)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("ARYX"), Err("MAP rules must contain 512 base64 encoded bits"));
+        assert_eq!(parse(&"!".repeat(86)), Err("MAP rules must contain 512 base64 encoded bits"));
+    }
+}
@@ -0,0 +1,423 @@
+use na::{point, Point2};
+use crate::coordinate::Coordinate;
+use crate::quadtree::GlobalQuadtree;
+use crate::utils::crc32;
+
+/// Bytes starting every snapshot
+pub const MAGIC: &[u8; 4] = b"GOLS";
+
+/// Current layout version.
+/// It only changes when the layout itself breaks: new data is stored in new sections, skipped by older readers.
+pub const VERSION: u16 = 1;
+
+/// Sections written by universes
+pub const RULE_SECTION: u8 = 1;
+pub const TOPOLOGY_SECTION: u8 = 2;
+pub const UPDATE_AREA_SECTION: u8 = 3;
+pub const GENERATION_SECTION: u8 = 4;
+pub const CELLS_SECTION: u8 = 5;
+pub const UPDATES_SECTION: u8 = 6;
+
+/// Sections from this tag are free for applications, to store their own data along the universe
+pub const APPLICATION_SECTIONS: u8 = 128;
+
+/// Binary snapshot, made of tagged sections.
+///
+/// Layout is: magic bytes, version (u16 little endian), sections, then a CRC-32 checksum (u32 little endian)
+/// of all previous bytes. Each section is a tag byte, followed by its length (as a varint) and its content.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Snapshot {
+    sections: Vec<(u8, Vec<u8>)>,
+}
+
+// Methods
+impl Snapshot {
+    #[inline]
+    pub fn new() -> Snapshot {
+        Snapshot::default()
+    }
+
+    /// Returns decoder on content of section, if present
+    pub fn section(&self, tag: u8) -> Option<Decoder<'_>> {
+        self.sections.iter()
+            .find(|(section, _)| *section == tag)
+            .map(|(_, content)| Decoder::new(content))
+    }
+
+    /// Sets content of section, replacing previous one
+    pub fn set_section(&mut self, tag: u8, content: Encoder) {
+        self.sections.retain(|(section, _)| *section != tag);
+        self.sections.push((tag, content.into_bytes()));
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.write_bytes(MAGIC);
+        encoder.write_bytes(&VERSION.to_le_bytes());
+
+        for (tag, content) in &self.sections {
+            encoder.write_u8(*tag);
+            encoder.write_varint(content.len() as u64);
+            encoder.write_bytes(content);
+        }
+
+        let mut bytes = encoder.into_bytes();
+        let checksum = crc32(&bytes);
+        bytes.extend(checksum.to_le_bytes());
+
+        bytes
+    }
+
+    /// Reads snapshot, checking its magic bytes, version and checksum
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot, &'static str> {
+        if !bytes.starts_with(MAGIC) {
+            return Err("bytes are not a universe snapshot");
+        }
+
+        if bytes.len() < MAGIC.len() + 6 {
+            return Err("snapshot is truncated");
+        }
+
+        let (content, checksum) = bytes.split_at(bytes.len() - 4);
+
+        if crc32(content).to_le_bytes() != checksum {
+            return Err("snapshot is corrupted");
+        }
+
+        let mut decoder = Decoder::new(&content[MAGIC.len()..]);
+        let version = u16::from_le_bytes([decoder.read_u8()?, decoder.read_u8()?]);
+
+        if version == 0 || version > VERSION {
+            return Err("snapshot version is not supported");
+        }
+
+        let mut snapshot = Snapshot::new();
+
+        while !decoder.is_empty() {
+            let tag = decoder.read_u8()?;
+            let length = decoder.read_varint()?;
+            let content = decoder.read_bytes(usize::try_from(length).map_err(|_| "snapshot is truncated")?)?;
+
+            snapshot.sections.push((tag, content.to_vec()));
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// Writes section content
+#[derive(Clone, Debug, Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    #[inline]
+    pub fn new() -> Encoder {
+        Encoder::default()
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    #[inline]
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    #[inline]
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Writes unsigned integer using 7 bits per byte, the highest one marking following bytes
+    pub fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+
+        self.bytes.push(value as u8);
+    }
+
+    /// Writes signed integer as a zigzag encoded varint, so that small negative values stay small
+    #[inline]
+    pub fn write_signed(&mut self, value: i64) {
+        self.write_varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    #[inline]
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Writes string, prefixed by its length
+    pub fn write_str(&mut self, value: &str) {
+        self.write_varint(value.len() as u64);
+        self.write_bytes(value.as_bytes());
+    }
+
+    /// Writes point, whatever its coordinate type
+    pub fn write_point<C: Coordinate>(&mut self, point: &Point2<C>) {
+        self.write_signed(widen(point.x));
+        self.write_signed(widen(point.y));
+    }
+
+    /// Writes cells positions, then their states
+    pub fn write_cells<C: Coordinate>(&mut self, cells: &GlobalQuadtree<u8, C>) {
+        let mut cells = cells.iter().collect::<Vec<_>>();
+        cells.sort_by_key(|(pt, _)| (pt.y, pt.x));
+
+        self.write_varint(cells.len() as u64);
+        self.write_rows(cells.iter().map(|&(pt, _)| pt));
+
+        for (_, &state) in cells {
+            self.write_u8(state);
+        }
+    }
+
+    /// Writes points, in any order
+    pub fn write_points<C: Coordinate>(&mut self, points: &[Point2<C>]) {
+        let mut points = points.iter().collect::<Vec<_>>();
+        points.sort_by_key(|pt| (pt.y, pt.x));
+
+        self.write_varint(points.len() as u64);
+        self.write_rows(points.into_iter());
+    }
+
+    /// Writes points sorted by rows: only gaps are stored between points of the same row
+    fn write_rows<'a, C: Coordinate>(&mut self, points: impl Iterator<Item = &'a Point2<C>>) {
+        let mut previous: Option<&Point2<C>> = None;
+
+        for pt in points {
+            match previous {
+                None => self.write_point(pt),
+                Some(prev) if prev.y == pt.y => {
+                    self.write_varint(0);
+                    self.write_varint(pt.x.abs_diff(prev.x) - 1);
+                },
+                Some(prev) => {
+                    self.write_varint(pt.y.abs_diff(prev.y));
+                    self.write_signed(widen(pt.x));
+                },
+            }
+
+            previous = Some(pt);
+        }
+    }
+}
+
+/// Reads section content
+#[derive(Clone, Debug)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Decoder<'a> {
+        Decoder { bytes }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], &'static str> {
+        if count > self.bytes.len() {
+            return Err("snapshot is truncated");
+        }
+
+        let (bytes, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+
+        Ok(bytes)
+    }
+
+    #[inline]
+    pub fn read_u8(&mut self) -> Result<u8, &'static str> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    pub fn read_varint(&mut self) -> Result<u64, &'static str> {
+        let mut value = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err("snapshot holds a too large integer")
+    }
+
+    #[inline]
+    pub fn read_signed(&mut self) -> Result<i64, &'static str> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    pub fn read_f64(&mut self) -> Result<f64, &'static str> {
+        let bytes = self.read_bytes(8)?;
+        Ok(f64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn read_str(&mut self) -> Result<&'a str, &'static str> {
+        let length = self.read_varint()?;
+        let bytes = self.read_bytes(usize::try_from(length).map_err(|_| "snapshot is truncated")?)?;
+
+        std::str::from_utf8(bytes).map_err(|_| "snapshot holds invalid text")
+    }
+
+    /// Reads point, failing if it does not fit in coordinate type
+    pub fn read_point<C: Coordinate>(&mut self) -> Result<Point2<C>, &'static str> {
+        let x = self.read_signed()?;
+        let y = self.read_signed()?;
+
+        Ok(point![narrow(x)?, narrow(y)?])
+    }
+
+    /// Reads cells written by [`Encoder::write_cells`]
+    pub fn read_cells<C: Coordinate>(&mut self) -> Result<GlobalQuadtree<u8, C>, &'static str> {
+        let points = self.read_points()?;
+        let states = self.read_bytes(points.len())?;
+
+        Ok(points.into_iter().zip(states.iter().copied()).collect())
+    }
+
+    /// Reads points written by [`Encoder::write_points`], sorted by rows
+    pub fn read_points<C: Coordinate>(&mut self) -> Result<Vec<Point2<C>>, &'static str> {
+        let count = self.read_varint()?;
+        let mut points = Vec::new();
+        let mut previous: Option<(i64, i64)> = None;
+
+        for _ in 0..count {
+            let (x, y) = match previous {
+                None => (self.read_signed()?, self.read_signed()?),
+                Some((x, y)) => match self.read_varint()? {
+                    0 => {
+                        let x = x.checked_add_unsigned(self.read_varint()?).and_then(|x| x.checked_add(1));
+                        (x.ok_or("snapshot exceeds coordinate limits")?, y)
+                    },
+                    dy => {
+                        let x = self.read_signed()?;
+                        (x, y.checked_add_unsigned(dy).ok_or("snapshot exceeds coordinate limits")?)
+                    },
+                },
+            };
+
+            points.push(point![narrow(x)?, narrow(y)?]);
+            previous = Some((x, y));
+        }
+
+        Ok(points)
+    }
+}
+
+// Utils
+/// Converts coordinate to i64
+pub fn widen<C: Coordinate>(value: C) -> i64 {
+    let shift = u64::BITS - C::BITS;
+    ((value.to_bits() << shift) as i64) >> shift
+}
+
+/// Converts i64 to coordinate, failing if it does not fit
+pub fn narrow<C: Coordinate>(value: i64) -> Result<C, &'static str> {
+    let coordinate = C::from_bits(value as u64);
+
+    if widen(coordinate) == value {
+        Ok(coordinate)
+    } else {
+        Err("snapshot exceeds coordinate limits")
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        let mut encoder = Encoder::new();
+        encoder.write_varint(300);
+        encoder.write_signed(-2);
+        encoder.write_signed(i64::MIN);
+        encoder.write_str("B3/S23");
+        encoder.write_f64(5.5);
+        encoder.write_point(&point![-1, i32::MAX]);
+
+        let bytes = encoder.into_bytes();
+        assert_eq!(bytes[..3], [0xac, 0x02, 0x03]);
+
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(decoder.read_varint(), Ok(300));
+        assert_eq!(decoder.read_signed(), Ok(-2));
+        assert_eq!(decoder.read_signed(), Ok(i64::MIN));
+        assert_eq!(decoder.read_str(), Ok("B3/S23"));
+        assert_eq!(decoder.read_f64(), Ok(5.5));
+        assert_eq!(decoder.clone().read_point::<i64>(), Ok(point![-1, i32::MAX as i64]));
+        assert_eq!(decoder.read_point::<i32>(), Ok(point![-1, i32::MAX]));
+        assert_eq!(decoder.read_u8(), Err("snapshot is truncated"));
+
+        assert_eq!(narrow::<i32>(1 << 31), Err("snapshot exceeds coordinate limits"));
+    }
+
+    #[test]
+    fn test_cells() {
+        let cells = [(point![-3, 2], 1), (point![i64::MAX, 2], 3), (point![-1, i64::MIN], 1), (point![0, 0], 2), (point![5, 0], 1)]
+            .into_iter()
+            .collect::<GlobalQuadtree<u8, i64>>();
+
+        let mut encoder = Encoder::new();
+        encoder.write_cells(&cells);
+
+        encoder.write_points(&[point![2, 1], point![-1, 1], point![0, 3]]);
+
+        let bytes = encoder.into_bytes();
+        let mut decoder = Decoder::new(&bytes);
+
+        assert_eq!(decoder.read_cells(), Ok(cells));
+        assert_eq!(decoder.read_points(), Ok(vec![point![-1, 1], point![2, 1], point![0, 3]]));
+        assert_eq!(Decoder::new(&bytes).read_cells::<i32>(), Err("snapshot exceeds coordinate limits"));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut content = Encoder::new();
+        content.write_str("hello");
+
+        let mut snapshot = Snapshot::new();
+        snapshot.set_section(APPLICATION_SECTIONS, content);
+
+        let mut bytes = snapshot.to_bytes();
+        assert_eq!(bytes[..6], [b'G', b'O', b'L', b'S', 1, 0]);
+        assert_eq!(Snapshot::from_bytes(&bytes).as_ref(), Ok(&snapshot));
+        assert_eq!(snapshot.section(APPLICATION_SECTIONS).unwrap().read_str(), Ok("hello"));
+        assert!(snapshot.section(1).is_none());
+
+        // Checks
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(Snapshot::from_bytes(b"GOL"), Err("bytes are not a universe snapshot"));
+        assert_eq!(Snapshot::from_bytes(b"GOLS\x01\x00"), Err("snapshot is truncated"));
+        assert_eq!(Snapshot::from_bytes(&bytes[..bytes.len() - 1]), Err("snapshot is corrupted"));
+
+        bytes[8] = b'j';
+        assert_eq!(Snapshot::from_bytes(&bytes), Err("snapshot is corrupted"));
+
+        let mut newer = Encoder::new();
+        newer.write_bytes(MAGIC);
+        newer.write_bytes(&(VERSION + 1).to_le_bytes());
+
+        let mut newer = newer.into_bytes();
+        newer.extend(crc32(&newer).to_le_bytes());
+
+        assert_eq!(Snapshot::from_bytes(&newer), Err("snapshot version is not supported"));
+    }
+}
@@ -0,0 +1,86 @@
+use std::ops::Not;
+use na::{point, Point2};
+use crate::coordinate::Coordinate;
+
+/// Rotations and flips of the plane.
+/// They are applied around the corner shared by the 4 cells surrounding origin, mapping x to !x (= -1 - x)
+/// so that no coordinate overflows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    /// Quarter turn, from x axis to y axis
+    Rotate90,
+    /// Half turn
+    Rotate180,
+    /// Quarter turn, from y axis to x axis
+    Rotate270,
+    /// Mirrors x axis
+    FlipHorizontal,
+    /// Mirrors y axis
+    FlipVertical,
+    /// Swaps x and y axis
+    FlipDiagonal,
+    /// Swaps and mirrors x and y axis
+    FlipAntiDiagonal,
+}
+
+// Methods
+impl Symmetry {
+    /// Maps given point
+    #[inline]
+    pub fn apply<C: Coordinate>(&self, point: &Point2<C>) -> Point2<C> {
+        let (x, y) = self.map(point.x, point.y);
+        point![x, y]
+    }
+
+    /// Maps given quarter index (x bit being 0b10 and y bit 0b01)
+    #[inline]
+    pub fn apply_quarter(&self, quarter: usize) -> usize {
+        let (x, y) = self.map(quarter & 0b10 != 0, quarter & 0b01 != 0);
+        ((x as usize) << 1) | y as usize
+    }
+
+    /// Test if symmetry swaps axis
+    #[inline]
+    pub fn swaps_axis(&self) -> bool {
+        matches!(self, Symmetry::Rotate90 | Symmetry::Rotate270 | Symmetry::FlipDiagonal | Symmetry::FlipAntiDiagonal)
+    }
+
+    // Utils
+    fn map<V: Not<Output = V>>(&self, x: V, y: V) -> (V, V) {
+        match self {
+            Symmetry::Rotate90 => (!y, x),
+            Symmetry::Rotate180 => (!x, !y),
+            Symmetry::Rotate270 => (y, !x),
+            Symmetry::FlipHorizontal => (!x, y),
+            Symmetry::FlipVertical => (x, !y),
+            Symmetry::FlipDiagonal => (y, x),
+            Symmetry::FlipAntiDiagonal => (!y, !x),
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(Symmetry::Rotate90.apply(&point![3, 1]), point![-2, 3]);
+        assert_eq!(Symmetry::Rotate180.apply(&point![3, 1]), point![-4, -2]);
+        assert_eq!(Symmetry::FlipDiagonal.apply(&point![3, 1]), point![1, 3]);
+        assert_eq!(Symmetry::FlipHorizontal.apply(&point![i32::MIN, 1]), point![i32::MAX, 1]);
+    }
+
+    #[test]
+    fn test_apply_quarter() {
+        for symmetry in [Symmetry::Rotate90, Symmetry::Rotate270, Symmetry::FlipVertical, Symmetry::FlipAntiDiagonal] {
+            for quarter in 0..4 {
+                let point = point![(quarter >> 1) as i32 - 1, (quarter & 1) as i32 - 1];
+                let expected = symmetry.apply(&point);
+
+                assert_eq!(symmetry.apply_quarter(quarter), ((expected.x >= 0) as usize) << 1 | (expected.y >= 0) as usize);
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+use std::str::FromStr;
+use na::{point, Point2, Vector2};
+use crate::coordinate::{checked_offset, Coordinate};
+use crate::neighborhood::Neighborhood;
+
+/// Shape of the universe space.
+/// Finite topologies span from origin (included) to their size (excluded).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Topology {
+    /// Unbounded plane
+    #[default]
+    Plane,
+    /// Finite plane, surrounded by dead cells
+    Bounded(Vector2<i32>),
+    /// Wraps both axis
+    Torus(Vector2<i32>),
+    /// Wraps x axis, unbounded on y axis
+    Cylinder(i32),
+    /// Wraps both axis, flipping x when wrapping on y axis
+    KleinBottle(Vector2<i32>),
+    /// Wraps both axis, flipping the other axis when wrapping. Corners wrapped on both axis are dead.
+    CrossSurface(Vector2<i32>),
+}
+
+// Methods
+impl Topology {
+    /// Finite plane, checking its size is positive
+    pub fn bounded(size: Vector2<i32>) -> Result<Topology, &'static str> {
+        Ok(Topology::Bounded(check_size(size)?))
+    }
+
+    /// Torus, checking its size is positive
+    pub fn torus(size: Vector2<i32>) -> Result<Topology, &'static str> {
+        Ok(Topology::Torus(check_size(size)?))
+    }
+
+    /// Cylinder, checking its width is positive
+    pub fn cylinder(width: i32) -> Result<Topology, &'static str> {
+        Ok(Topology::Cylinder(check_size(Vector2::new(width, 1))?.x))
+    }
+
+    /// Klein bottle, checking its size is positive
+    pub fn klein_bottle(size: Vector2<i32>) -> Result<Topology, &'static str> {
+        Ok(Topology::KleinBottle(check_size(size)?))
+    }
+
+    /// Cross surface, checking its size is positive
+    pub fn cross_surface(size: Vector2<i32>) -> Result<Topology, &'static str> {
+        Ok(Topology::CrossSurface(check_size(size)?))
+    }
+
+    /// Maps given point into the universe space.
+    /// Returns None if point lies on a dead border.
+    pub fn wrap<C: Coordinate>(&self, point: Point2<C>) -> Option<Point2<C>> {
+        match *self {
+            Topology::Plane => Some(point),
+            Topology::Bounded(size) => {
+                let inside = |coord: C, size: i32| coord >= C::ZERO && coord < C::from_i32(size);
+
+                if inside(point.x, size.x) && inside(point.y, size.y) {
+                    Some(point)
+                } else {
+                    None
+                }
+            },
+            Topology::Torus(size) => Some(point![fold(point.x, size.x).0, fold(point.y, size.y).0]),
+            Topology::Cylinder(width) => Some(point![fold(point.x, width).0, point.y]),
+            Topology::KleinBottle(size) => {
+                let (x, _) = fold(point.x, size.x);
+                let (y, flip_x) = fold(point.y, size.y);
+
+                Some(point![flip(x, size.x, flip_x), y])
+            },
+            Topology::CrossSurface(size) => {
+                let outside = |coord: C, size: i32| coord < C::ZERO || coord >= C::from_i32(size);
+
+                // Like golly, corners wrapped on both axis are dead (they would map onto the cell itself)
+                if outside(point.x, size.x) && outside(point.y, size.y) {
+                    return None;
+                }
+
+                let (x, flip_y) = fold(point.x, size.x);
+                let (y, flip_x) = fold(point.y, size.y);
+
+                Some(point![flip(x, size.x, flip_x), flip(y, size.y, flip_y)])
+            },
+        }
+    }
+
+    /// Test if all neighbors within range of given point are in the universe space, without wrapping
+    /// nor crossing coordinate limits
+    pub fn is_interior<C: Coordinate>(&self, point: &Point2<C>, range: i32) -> bool {
+        let inside = |coord: C, size: i32| coord >= C::from_i32(range) && coord < C::from_i32(size - range);
+        let within_limits = |coord: C| coord >= C::MIN + C::from_i32(range) && coord <= C::MAX - C::from_i32(range);
+
+        match *self {
+            Topology::Plane => within_limits(point.x) && within_limits(point.y),
+            Topology::Cylinder(width) => inside(point.x, width) && within_limits(point.y),
+            Topology::Bounded(size)
+            | Topology::Torus(size)
+            | Topology::KleinBottle(size)
+            | Topology::CrossSurface(size) => inside(point.x, size.x) && inside(point.y, size.y),
+        }
+    }
+
+    /// Iterates over the neighbors of given point, mapped into the universe space.
+    /// Neighbors beyond coordinate limits are skipped, as if lying on a dead border.
+    pub fn neighbors<C: Coordinate>(&self, point: Point2<C>, neighborhood: &Neighborhood) -> impl Iterator<Item = Point2<C>> + '_ {
+        neighborhood.offsets()
+            .filter_map(move |offset| checked_offset(&point, &offset).and_then(|pt| self.wrap(pt)))
+    }
+
+    /// Iterates over given point and its neighbors, mapped into the universe space
+    pub fn around<C: Coordinate>(&self, point: Point2<C>, neighborhood: &Neighborhood) -> impl Iterator<Item = Point2<C>> + '_ {
+        self.wrap(point).into_iter().chain(self.neighbors(point, neighborhood))
+    }
+}
+
+// Utils
+/// Maps coordinate into 0..size, also returning if it crossed an odd number of borders
+fn fold<C: Coordinate>(coord: C, size: i32) -> (C, bool) {
+    let size = C::from_i32(size);
+    let odd = coord.div_euclid(size).rem_euclid(C::from_i32(2)) != C::ZERO;
+
+    (coord.rem_euclid(size), odd)
+}
+
+/// Mirrors coordinate (inside 0..size) if asked to
+fn flip<C: Coordinate>(coord: C, size: i32, flip: bool) -> C {
+    if flip {
+        C::from_i32(size - 1) - coord
+    } else {
+        coord
+    }
+}
+
+/// Checks both dimensions of a finite topology are positive
+fn check_size(size: Vector2<i32>) -> Result<Vector2<i32>, &'static str> {
+    if size.x <= 0 || size.y <= 0 {
+        return Err("topology size must be positive");
+    }
+
+    Ok(size)
+}
+
+fn parse_size(width: &str, height: &str, allow_null_height: bool) -> Result<Vector2<i32>, &'static str> {
+    let width = width.parse::<i32>().map_err(|_| "topology size must be made of integers")?;
+    let height = height.parse::<i32>().map_err(|_| "topology size must be made of integers")?;
+
+    if height == 0 && allow_null_height {
+        return check_size(Vector2::new(width, 1)).map(|_| Vector2::new(width, 0));
+    }
+
+    check_size(Vector2::new(width, height))
+}
+
+impl FromStr for Topology {
+    type Err = &'static str;
+
+    /// Parses golly's topology notation (like T100,80)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.is_empty() {
+            return Ok(Topology::Plane);
+        }
+
+        let kind = s.chars().next().unwrap_or_default();
+        let (width, height) = s[kind.len_utf8()..].split_once(',')
+            .ok_or("topology size must be 2 integers separated by ','")?;
+
+        match kind {
+            'P' | 'p' => Ok(Topology::Bounded(parse_size(width, height, false)?)),
+            'T' | 't' => {
+                let size = parse_size(width, height, true)?;
+
+                if size.y == 0 {
+                    Ok(Topology::Cylinder(size.x))
+                } else {
+                    Ok(Topology::Torus(size))
+                }
+            },
+            'K' | 'k' => {
+                let width = width.strip_suffix('*')
+                    .ok_or("only klein bottles twisted on horizontal edges (K<w>*,<h>) are supported")?;
+
+                Ok(Topology::KleinBottle(parse_size(width, height, false)?))
+            },
+            'C' | 'c' => Ok(Topology::CrossSurface(parse_size(width, height, false)?)),
+            _ => Err("unknown topology"),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topology::Plane => Ok(()),
+            Topology::Bounded(size) => write!(f, "P{},{}", size.x, size.y),
+            Topology::Torus(size) => write!(f, "T{},{}", size.x, size.y),
+            Topology::Cylinder(width) => write!(f, "T{width},0"),
+            Topology::KleinBottle(size) => write!(f, "K{}*,{}", size.x, size.y),
+            Topology::CrossSurface(size) => write!(f, "C{},{}", size.x, size.y),
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_bounded() {
+        let topology = Topology::Bounded(Vector2::new(4, 3));
+
+        assert_eq!(topology.wrap(point![0, 0]), Some(point![0, 0]));
+        assert_eq!(topology.wrap(point![3, 2]), Some(point![3, 2]));
+        assert_eq!(topology.wrap(point![-1, 0]), None);
+        assert_eq!(topology.wrap(point![4, 0]), None);
+        assert_eq!(topology.wrap(point![0, 3]), None);
+    }
+
+    #[test]
+    fn test_wrap_torus() {
+        let topology = Topology::Torus(Vector2::new(4, 3));
+
+        assert_eq!(topology.wrap(point![-1, -1]), Some(point![3, 2]));
+        assert_eq!(topology.wrap(point![4, 3]), Some(point![0, 0]));
+        assert_eq!(topology.wrap(point![2, 1]), Some(point![2, 1]));
+    }
+
+    #[test]
+    fn test_wrap_cylinder() {
+        let topology = Topology::Cylinder(4);
+
+        assert_eq!(topology.wrap(point![-1, -10]), Some(point![3, -10]));
+        assert_eq!(topology.wrap(point![4, 50]), Some(point![0, 50]));
+    }
+
+    #[test]
+    fn test_wrap_klein_bottle() {
+        let topology = Topology::KleinBottle(Vector2::new(4, 3));
+
+        assert_eq!(topology.wrap(point![-1, 1]), Some(point![3, 1]));
+        assert_eq!(topology.wrap(point![0, 3]), Some(point![3, 0]));
+        assert_eq!(topology.wrap(point![1, -1]), Some(point![2, 2]));
+    }
+
+    #[test]
+    fn test_wrap_cross_surface() {
+        let topology = Topology::CrossSurface(Vector2::new(4, 3));
+
+        assert_eq!(topology.wrap(point![-1, 0]), Some(point![3, 2]));
+        assert_eq!(topology.wrap(point![0, 3]), Some(point![3, 0]));
+        assert_eq!(topology.wrap(point![1, 1]), Some(point![1, 1]));
+        assert_eq!(topology.wrap(point![-1, -1]), None);
+        assert_eq!(topology.wrap(point![4, 3]), None);
+    }
+
+    #[test]
+    fn test_neighbors_cross_surface_corner() {
+        let topology = Topology::CrossSurface(Vector2::new(4, 3));
+        let neighbors = topology.neighbors(point![0, 0], &Neighborhood::moore())
+            .collect::<Vec<_>>();
+
+        assert_eq!(neighbors.len(), 7);
+        assert!(!neighbors.contains(&point![0, 0]));
+    }
+
+    #[test]
+    fn test_is_interior() {
+        let topology = Topology::Torus(Vector2::new(4, 4));
+
+        assert!(topology.is_interior(&point![1, 2], 1));
+        assert!(!topology.is_interior(&point![0, 2], 1));
+        assert!(!topology.is_interior(&point![1, 3], 1));
+        assert!(!topology.is_interior(&point![1, 2], 2));
+        assert!(Topology::Plane.is_interior(&point![i32::MAX - 1, 0], 1));
+        assert!(!Topology::Plane.is_interior(&point![i32::MAX, 0], 1));
+        assert!(!Topology::Plane.is_interior(&point![0, i64::MIN], 1));
+    }
+
+    #[test]
+    fn test_neighbors_at_limits() {
+        let neighbors = Topology::Plane.neighbors(point![i32::MAX, i32::MIN], &Neighborhood::moore())
+            .collect::<Vec<_>>();
+
+        assert_eq!(neighbors.len(), 3);
+        assert!(neighbors.contains(&point![i32::MAX - 1, i32::MIN + 1]));
+
+        // Finite topologies are not affected by the coordinate type
+        assert_eq!(Topology::Torus(Vector2::new(4, 3)).wrap(point![-1i64, -1]), Some(point![3, 2]));
+    }
+
+    #[test]
+    fn test_constructors() {
+        assert_eq!(Topology::torus(Vector2::new(30, 20)), Ok(Topology::Torus(Vector2::new(30, 20))));
+        assert_eq!(Topology::cylinder(30), Ok(Topology::Cylinder(30)));
+
+        assert_eq!(Topology::bounded(Vector2::new(0, 20)), Err("topology size must be positive"));
+        assert_eq!(Topology::torus(Vector2::new(30, -1)), Err("topology size must be positive"));
+        assert_eq!(Topology::cylinder(0), Err("topology size must be positive"));
+        assert_eq!(Topology::klein_bottle(Vector2::new(-30, 20)), Err("topology size must be positive"));
+        assert_eq!(Topology::cross_surface(Vector2::new(30, 0)), Err("topology size must be positive"));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("".parse(), Ok(Topology::Plane));
+        assert_eq!("P30,20".parse(), Ok(Topology::Bounded(Vector2::new(30, 20))));
+        assert_eq!("T30,20".parse(), Ok(Topology::Torus(Vector2::new(30, 20))));
+        assert_eq!("T30,0".parse(), Ok(Topology::Cylinder(30)));
+        assert_eq!("K30*,20".parse(), Ok(Topology::KleinBottle(Vector2::new(30, 20))));
+        assert_eq!("C30,20".parse(), Ok(Topology::CrossSurface(Vector2::new(30, 20))));
+
+        assert_eq!("T30".parse::<Topology>(), Err("topology size must be 2 integers separated by ','"));
+        assert_eq!("P0,20".parse::<Topology>(), Err("topology size must be positive"));
+        assert_eq!("K30,20*".parse::<Topology>(), Err("only klein bottles twisted on horizontal edges (K<w>*,<h>) are supported"));
+        assert_eq!("S30,30".parse::<Topology>(), Err("unknown topology"));
+    }
+
+    #[test]
+    fn test_display() {
+        for topology in ["", "P30,20", "T30,20", "T30,0", "K30*,20", "C30,20"] {
+            assert_eq!(topology.parse::<Topology>().unwrap().to_string(), topology);
+        }
+    }
+}
@@ -0,0 +1,1091 @@
+use std::cmp::min;
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::ops::RangeInclusive;
+use na::{point, Point2, Vector2};
+use py::Holds;
+use crate::changes::Changes;
+use crate::coordinate::{checked_offset, Coordinate};
+use crate::hashlife::Hashlife;
+use crate::history::{History, Statistics};
+use crate::neighborhood::Neighbors;
+use crate::pattern::{rle, ParseError, Pattern};
+use crate::quadtree::GlobalQuadtree;
+use crate::random::Rng;
+use crate::render::{self, png, Palette};
+use crate::rule::{ALIVE, DEAD, Rule};
+use crate::snapshot::{
+    Decoder, Encoder, Snapshot,
+    CELLS_SECTION, GENERATION_SECTION, RULE_SECTION, TOPOLOGY_SECTION, UPDATE_AREA_SECTION, UPDATES_SECTION
+};
+use crate::symmetry::Symmetry;
+use crate::topology::Topology;
+use crate::update_list::{UpdateArea, UpdateList};
+use crate::utils::cmp_xy_order;
+
+/// Life universe
+#[derive(Clone, Debug)]
+pub struct Universe<C: Coordinate = i32> {
+    cells: GlobalQuadtree<u8, C>,
+    hashlife: Option<Hashlife>,
+    history: History,
+    rule: Rule,
+    statistics: Statistics,
+    topology: Topology,
+    updates: UpdateList<C>,
+}
+
+impl<C: Coordinate> Universe<C> {
+    /// Builds a dead universe
+    pub fn new() -> Universe<C> {
+        Universe {
+            cells: GlobalQuadtree::new(),
+            hashlife: None,
+            history: History::default(),
+            rule: Rule::default(),
+            statistics: Statistics::default(),
+            topology: Topology::default(),
+            updates: UpdateList::new(),
+        }
+    }
+
+    /// Builds a random universe of given size, each cell being alive with given probability
+    pub fn random(size: Vector2<i32>, density: f64, rng: &mut Rng) -> Universe<C> {
+        let mut universe = Universe::new();
+
+        for row in 0..size.y {
+            for col in 0..size.x {
+                if rng.chance(density) {
+                    universe.set_alive(point![C::from_i32(col), C::from_i32(row)])
+                }
+            }
+        }
+
+        universe
+    }
+
+    /// Builds universe from given pattern, with its rule and topology
+    pub fn from_pattern(pattern: &Pattern<C>) -> Universe<C> {
+        let mut universe = Universe::new();
+        universe.rule = pattern.rule;
+        universe.topology = pattern.topology;
+        universe.replace_cells(pattern.cells.clone());
+
+        universe
+    }
+
+    /// Parses a run length encoded pattern
+    #[inline]
+    pub fn from_rle(input: &str) -> Result<Universe<C>, ParseError> {
+        rle::parse(input).map(|pattern| Universe::from_pattern(&pattern))
+    }
+
+    /// Exports cells, rule and topology as a pattern
+    pub fn to_pattern(&self) -> Pattern<C> {
+        Pattern {
+            name: None,
+            author: None,
+            comments: Vec::new(),
+            rule: self.rule,
+            topology: self.topology,
+            cells: self.cells.clone(),
+        }
+    }
+
+    /// Exports cells as a run length encoded pattern
+    #[inline]
+    pub fn to_rle(&self) -> String {
+        rle::write(&self.to_pattern())
+    }
+
+    /// Saves cells, rule, topology, update area, registered updates and generation counter
+    pub fn snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot::new();
+
+        let mut rule = Encoder::new();
+        rule.write_str(&self.rule.to_string());
+        snapshot.set_section(RULE_SECTION, rule);
+
+        let mut topology = Encoder::new();
+        topology.write_str(&self.topology.to_string());
+        snapshot.set_section(TOPOLOGY_SECTION, topology);
+
+        let mut area = Encoder::new();
+        write_bound(&mut area, &self.updates.area().0);
+        write_bound(&mut area, &self.updates.area().1);
+        snapshot.set_section(UPDATE_AREA_SECTION, area);
+
+        let mut generation = Encoder::new();
+        generation.write_varint(self.statistics.generation);
+        snapshot.set_section(GENERATION_SECTION, generation);
+
+        let mut cells = Encoder::new();
+        cells.write_cells(&self.cells);
+        snapshot.set_section(CELLS_SECTION, cells);
+
+        let mut updates = Encoder::new();
+        updates.write_points(&self.updates.iter().copied().collect::<Vec<_>>());
+        snapshot.set_section(UPDATES_SECTION, updates);
+
+        snapshot
+    }
+
+    /// Restores universe from snapshot. Missing sections keep their default value, unknown ones are ignored.
+    pub fn from_snapshot(snapshot: &Snapshot) -> Result<Universe<C>, &'static str> {
+        let mut universe = Universe::new();
+
+        if let Some(mut section) = snapshot.section(RULE_SECTION) {
+            universe.rule = section.read_str()?.parse()?;
+        }
+
+        if let Some(mut section) = snapshot.section(TOPOLOGY_SECTION) {
+            universe.topology = section.read_str()?.parse()?;
+        }
+
+        if let Some(mut section) = snapshot.section(UPDATE_AREA_SECTION) {
+            let area = (read_bound(&mut section)?, read_bound(&mut section)?);
+            universe.updates = UpdateList::inside(area);
+        }
+
+        if let Some(mut section) = snapshot.section(GENERATION_SECTION) {
+            universe.statistics.generation = section.read_varint()?;
+        }
+
+        if let Some(mut section) = snapshot.section(CELLS_SECTION) {
+            let cells = section.read_cells()?;
+
+            if cells.iter().any(|(_, &state)| state == DEAD || state >= universe.rule.states()) {
+                return Err("snapshot holds cell states not supported by its rule");
+            }
+
+            universe.replace_cells(cells);
+        }
+
+        // Without registered cells, all cells are registered, which only costs some useless updates
+        if let Some(mut section) = snapshot.section(UPDATES_SECTION) {
+            let points = section.read_points()?;
+
+            universe.updates = UpdateList::inside(*universe.updates.area());
+            points.into_iter().for_each(|pt| universe.updates.register(pt));
+        }
+
+        Ok(universe)
+    }
+
+    /// Saves universe as a compact binary snapshot
+    #[inline]
+    pub fn serialize(&self) -> Vec<u8> {
+        self.snapshot().to_bytes()
+    }
+
+    /// Restores universe from a binary snapshot
+    #[inline]
+    pub fn deserialize(bytes: &[u8]) -> Result<Universe<C>, &'static str> {
+        Universe::from_snapshot(&Snapshot::from_bytes(bytes)?)
+    }
+
+    /// Renders cells of area as a png image, each cell being a square of given size (in pixels).
+    /// First row and column of the area are drawn on top left corner.
+    pub fn render_png(&self, area: &RangeInclusive<Point2<C>>, cell_size: u32, palette: &Palette) -> Result<Vec<u8>, &'static str> {
+        let (start, end) = (area.start(), area.end());
+
+        if start.x > end.x || start.y > end.y || cell_size == 0 {
+            return Err("cannot render an empty image");
+        }
+
+        let side = |size: u64| size.checked_add(1)
+            .and_then(|size| size.checked_mul(cell_size as u64))
+            .filter(|&size| size <= render::MAX_PIXELS);
+
+        let (width, height) = match (side(end.x.abs_diff(start.x)), side(end.y.abs_diff(start.y))) {
+            (Some(width), Some(height)) if width * height <= render::MAX_PIXELS => (width as usize, height as usize),
+            _ => return Err("image is too large to be rendered"),
+        };
+
+        let cell_size = cell_size as usize;
+        let mut pixels = vec![DEAD; width * height];
+        let mut states = 2;
+
+        for (cell, &state) in self.cells.query(area) {
+            let x = cell.x.abs_diff(start.x) as usize * cell_size;
+            let y = cell.y.abs_diff(start.y) as usize * cell_size;
+
+            for row in y..y + cell_size {
+                pixels[row * width + x..row * width + x + cell_size].fill(state);
+            }
+
+            states = states.max(state as usize + 1);
+        }
+
+        let colors = (0..states).map(|state| palette.state_color(state as u8)).collect::<Vec<_>>();
+
+        Ok(png::encode(width as u32, height as u32, &colors, &pixels))
+    }
+
+    /// Returns living cells
+    #[inline]
+    pub fn cells(&self) -> &GlobalQuadtree<u8, C> {
+        &self.cells
+    }
+
+    /// Returns smallest area holding all living and dying cells, if any
+    #[inline]
+    pub fn bounding_box(&self) -> Option<RangeInclusive<Point2<C>>> {
+        self.cells.bounding_box()
+    }
+
+    /// Compacts cells storage, releasing memory kept from dead cells
+    #[inline]
+    pub fn shrink(&mut self) {
+        self.cells.shrink();
+    }
+
+    /// Number of generations computed since creation
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.statistics.generation
+    }
+
+    /// Number of alive cells
+    #[inline]
+    pub fn population(&self) -> usize {
+        self.statistics.population
+    }
+
+    /// Number of cells born during last generation
+    #[inline]
+    pub fn births(&self) -> usize {
+        self.statistics.births
+    }
+
+    /// Number of cells dead during last generation
+    #[inline]
+    pub fn deaths(&self) -> usize {
+        self.statistics.deaths
+    }
+
+    /// Returns statistics of last computed generations
+    #[inline]
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Changes number of generations kept in history
+    #[inline]
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history.set_capacity(capacity);
+    }
+
+    #[inline]
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Changes universe rule, registering all cells for update if neighborhood changes
+    pub fn set_rule(&mut self, rule: Rule) {
+        let neighborhood = self.rule.neighborhood();
+        self.rule = rule;
+
+        if rule.neighborhood() != neighborhood {
+            self.reset_updates();
+        }
+    }
+
+    #[inline]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Changes universe topology, killing cells outside of the new space
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+
+        let outside = self.cells.iter()
+            .map(|(&cell, _)| cell)
+            .filter(|&cell| topology.wrap(cell) != Some(cell))
+            .collect::<Vec<_>>();
+
+        for cell in outside {
+            self.cells.remove(&cell);
+        }
+
+        self.statistics.population = self.count_population();
+        self.reset_updates();
+    }
+
+    /// Return current update area
+    #[inline]
+    pub fn update_area(&self) -> &UpdateArea<C> {
+        self.updates.area()
+    }
+
+    /// Changes update area, cells outside of it will not evolve
+    pub fn set_update_area(&mut self, area: UpdateArea<C>) {
+        let old = self.updates.change_area(area);
+        let neighborhood = self.rule.neighborhood();
+
+        for (&cell, _) in self.cells.iter() {
+            if !old.holds(&cell) {
+                self.topology.around(cell, &neighborhood)
+                    .for_each(|pt| self.updates.register(pt));
+            };
+        }
+    }
+
+    /// Returns state of cell at given point (0 for dead, 1 for alive, others for dying)
+    #[inline]
+    pub fn state(&self, point: &Point2<C>) -> u8 {
+        self.cells.get(point).copied().unwrap_or(DEAD)
+    }
+
+    /// Test if cell at given point is alive
+    #[inline]
+    pub fn is_alive(&self, point: &Point2<C>) -> bool {
+        self.state(point) == ALIVE
+    }
+
+    /// Set state of cell at given point.
+    /// Point is mapped into the universe space, nothing happens if it lies on a dead border.
+    pub fn set_state(&mut self, point: Point2<C>, state: u8) {
+        if let Some(point) = self.topology.wrap(point) {
+            match (self.state(&point) == ALIVE, state == ALIVE) {
+                (false, true) => self.statistics.population += 1,
+                (true, false) => self.statistics.population -= 1,
+                _ => (),
+            }
+
+            if state == DEAD {
+                self.cells.remove(&point);
+            } else {
+                self.cells.insert(point, state);
+            }
+
+            self.updates.register_with_neighbors(point, &self.topology, &self.rule.neighborhood());
+        }
+    }
+
+    /// Set cell at given point alive
+    #[inline]
+    pub fn set_alive(&mut self, point: Point2<C>) {
+        self.set_state(point, ALIVE);
+    }
+
+    /// Set cell at given point dead
+    #[inline]
+    pub fn set_dead(&mut self, point: Point2<C>) {
+        self.set_state(point, DEAD);
+    }
+
+    /// Inserts some cells around given position, returning inserted cells.
+    /// Cells beyond coordinate limits are skipped.
+    pub fn insert_around(&mut self, center: Point2<C>, r: i32, density: f64, rng: &mut Rng) -> Changes<C> {
+        let topology = self.topology;
+        let mut changes = Changes::new();
+
+        (-r..=r).flat_map(|x| (-r..=r).map(move |y| Vector2::new(x, y)))
+            .filter(|offset| offset.x * offset.x + offset.y * offset.y <= r * r)
+            .filter_map(|offset| checked_offset(&center, &offset))
+            .filter_map(|cell| topology.wrap(cell))
+            .for_each(|cell| {
+                if rng.chance(density) && self.state(&cell) == DEAD {
+                    self.set_alive(cell);
+                    changes.birth(cell);
+                }
+            });
+
+        changes
+    }
+
+    /// Kills all cells inside given area
+    pub fn clear_area(&mut self, area: &RangeInclusive<Point2<C>>) {
+        if self.cells.remove_area(area) > 0 {
+            self.statistics.population = self.count_population();
+            self.reset_updates();
+        }
+    }
+
+    /// Moves all cells by given offset. Cells moved beyond coordinate limits die.
+    pub fn translate(&mut self, offset: &Vector2<C>) {
+        let cells = self.cells.translate(offset);
+        self.replace_cells(cells);
+    }
+
+    /// Rotates or flips all cells, around the corner shared by the 4 cells surrounding origin
+    pub fn transform(&mut self, symmetry: Symmetry) {
+        let cells = self.cells.transform(symmetry);
+        self.replace_cells(cells);
+    }
+
+    /// Moves cells inside given area by given offset, replacing cells at their destination
+    pub fn translate_area(&mut self, area: &RangeInclusive<Point2<C>>, offset: &Vector2<C>) {
+        let (selection, others) = self.split_cells(area);
+        let cells = selection.translate(offset).union(&others);
+
+        self.replace_cells(cells);
+    }
+
+    /// Rotates or flips cells inside given area, replacing cells at their destination.
+    /// Transformed area keeps the same first corner.
+    pub fn transform_area(&mut self, area: &RangeInclusive<Point2<C>>, symmetry: Symmetry) -> Result<(), &'static str> {
+        let (start, end) = (area.start(), area.end());
+
+        if end.x.checked_sub(start.x).is_none() || end.y.checked_sub(start.y).is_none() {
+            return Err("area is too large to be transformed");
+        }
+
+        let (first, last) = (symmetry.apply(start), symmetry.apply(end));
+        let anchor = point![min(first.x, last.x), min(first.y, last.y)];
+
+        let (selection, others) = self.split_cells(area);
+        let transformed = selection.transform(symmetry);
+
+        let transformed = match (start.x.checked_sub(anchor.x), start.y.checked_sub(anchor.y)) {
+            (Some(dx), Some(dy)) => transformed.translate(&Vector2::new(dx, dy)),
+            // Offset back to the first corner exceeds coordinate limits, so cells are moved one by one
+            _ => transformed.iter()
+                .map(|(pt, &state)| (point![start.x + (pt.x - anchor.x), start.y + (pt.y - anchor.y)], state))
+                .collect(),
+        };
+
+        self.replace_cells(transformed.union(&others));
+
+        Ok(())
+    }
+
+    /// Compute next state, returning changed cells
+    pub fn tick(&mut self) -> Changes<C> {
+        let updates = UpdateList::inside(*self.updates.area());
+        let updates = mem::replace(&mut self.updates, updates);
+
+        // Compute all next states before applying them, so every cell sees the current generation
+        let mut next_states = updates.iter()
+            .filter_map(|&cell| {
+                let (state, neighbors) = self.cell_state(&cell);
+                let next = self.rule.next_state(state, neighbors);
+
+                (next != state).then_some((cell, next))
+            })
+            .collect::<Vec<_>>();
+
+        // Updates are not ordered, only changes are sorted
+        next_states.sort_unstable_by(|(a, _), (b, _)| cmp_xy_order(a, b));
+
+        let mut changes = Changes::new();
+
+        for (cell, next) in next_states {
+            self.set_state(cell, next);
+            changes.record(cell, next);
+        }
+
+        self.record_statistics(1, changes.births().len(), changes.deaths().len());
+
+        changes
+    }
+
+    /// Computes given number of generations, returning net changes between current and final states
+    pub fn advance(&mut self, generations: u32) -> Changes<C> {
+        let mut initial = HashMap::new();
+
+        for _ in 0..generations {
+            let changes = self.tick();
+
+            // Each change tells the previous state of the cell
+            for &cell in changes.births() {
+                initial.entry(cell).or_insert(DEAD);
+            }
+
+            for &(cell, state) in changes.decays() {
+                initial.entry(cell).or_insert(state - 1);
+            }
+
+            for &cell in changes.deaths() {
+                initial.entry(cell).or_insert(self.rule.states() - 1);
+            }
+        }
+
+        let mut cells = initial.into_iter().collect::<Vec<_>>();
+        cells.sort_by(|(a, _), (b, _)| cmp_xy_order(a, b));
+
+        let mut changes = Changes::new();
+
+        for (cell, state) in cells {
+            let current = self.state(&cell);
+
+            if current != state {
+                changes.record(cell, current);
+            }
+        }
+
+        changes
+    }
+
+    /// Counts alive cells
+    fn count_population(&self) -> usize {
+        self.cells.iter()
+            .filter(|&(_, &state)| state == ALIVE)
+            .count()
+    }
+
+    /// Updates statistics after computing some generations, and adds them to history
+    fn record_statistics(&mut self, generations: u64, births: usize, deaths: usize) {
+        self.statistics = Statistics {
+            generation: self.statistics.generation + generations,
+            population: self.statistics.population,
+            births,
+            deaths,
+        };
+
+        self.history.push(self.statistics);
+    }
+
+    /// Splits cells between those inside given area and others
+    fn split_cells(&self, area: &RangeInclusive<Point2<C>>) -> (GlobalQuadtree<u8, C>, GlobalQuadtree<u8, C>) {
+        let mut others = self.cells.clone();
+        let selection = others.extract(area);
+
+        (selection, others)
+    }
+
+    /// Replaces all cells, mapping them into the universe space
+    fn replace_cells(&mut self, cells: GlobalQuadtree<u8, C>) {
+        self.cells = match self.topology {
+            Topology::Plane => cells,
+            topology => cells.iter()
+                .filter_map(|(&pt, &state)| topology.wrap(pt).map(|pt| (pt, state)))
+                .collect(),
+        };
+
+        self.statistics.population = self.count_population();
+        self.reset_updates();
+    }
+
+    /// Registers all cells and their neighbors for next update
+    fn reset_updates(&mut self) {
+        self.updates = UpdateList::inside(*self.updates.area());
+
+        for (&cell, _) in self.cells.iter() {
+            self.updates.register_with_neighbors(cell, &self.topology, &self.rule.neighborhood());
+        }
+    }
+
+    /// Get cell state and alive neighbors
+    fn cell_state(&self, point: &Point2<C>) -> (u8, Neighbors) {
+        let neighborhood = self.rule.neighborhood();
+        let range = neighborhood.range();
+
+        if !self.topology.is_interior(point, range) {
+            let neighbors = neighborhood.alive_neighbors(
+                neighborhood.offsets()
+                    .filter(|offset| {
+                        checked_offset(point, offset)
+                            .and_then(|pt| self.topology.wrap(pt))
+                            .is_some_and(|pt| self.is_alive(&pt))
+                    })
+            );
+
+            return (self.state(point), neighbors);
+        }
+
+        let offset = C::from_i32(range);
+        let area = point![point.x - offset, point.y - offset]..=point![point.x + offset, point.y + offset];
+        let mut state = DEAD;
+
+        let alive = self.cells.query(&area)
+            .inspect(|&(pt, &pt_state)| if pt == point { state = pt_state })
+            .filter(|&(pt, &pt_state)| pt != point && pt_state == ALIVE)
+            .map(|(pt, _)| (pt - point).map(C::as_i32))
+            .filter(|offset| neighborhood.contains(offset));
+
+        let neighbors = neighborhood.alive_neighbors(alive);
+
+        (state, neighbors)
+    }
+}
+
+impl Universe<i32> {
+    /// Advances universe by 2^step generations at once, using hashlife.
+    /// Whole universe evolves, regardless of the update area.
+    pub fn hashlife_step(&mut self, step: u32) -> Result<(), &'static str> {
+        if self.topology != Topology::Plane {
+            return Err("hashlife only supports the unbounded plane topology");
+        }
+
+        let hashlife = self.hashlife.get_or_insert_with(|| Hashlife::new(self.rule));
+        hashlife.set_rule(self.rule);
+
+        self.cells = hashlife.advance(&self.cells, step)?;
+        self.statistics.population = self.count_population();
+        self.reset_updates();
+
+        // Births and deaths are not tracked by hashlife
+        self.record_statistics(1 << step, 0, 0);
+
+        Ok(())
+    }
+}
+
+// Utils
+impl<C: Coordinate> Default for Universe<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes update area bound
+fn write_bound<C: Coordinate>(encoder: &mut Encoder, bound: &Bound<Point2<C>>) {
+    match bound {
+        Unbounded => encoder.write_u8(0),
+        Included(pt) => {
+            encoder.write_u8(1);
+            encoder.write_point(pt);
+        },
+        Excluded(pt) => {
+            encoder.write_u8(2);
+            encoder.write_point(pt);
+        },
+    }
+}
+
+/// Reads update area bound
+fn read_bound<C: Coordinate>(decoder: &mut Decoder) -> Result<Bound<Point2<C>>, &'static str> {
+    match decoder.read_u8()? {
+        0 => Ok(Unbounded),
+        1 => Ok(Included(decoder.read_point()?)),
+        2 => Ok(Excluded(decoder.read_point()?)),
+        _ => Err("snapshot holds an invalid update area"),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_blinker() {
+        let mut universe = Universe::new();
+        universe.set_alive(point![-1, 0]);
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+
+        let changes = universe.tick();
+
+        assert_eq!(changes.births(), &[point![0, -1], point![0, 1]]);
+        assert_eq!(changes.deaths(), &[point![-1, 0], point![1, 0]]);
+
+        let changes = universe.tick();
+
+        assert_eq!(changes.births(), &[point![-1, 0], point![1, 0]]);
+        assert_eq!(changes.deaths(), &[point![0, -1], point![0, 1]]);
+    }
+
+    #[test]
+    fn test_tick_generations() {
+        let mut universe = Universe::new();
+        universe.set_rule("B2/S/C3".parse().unwrap());
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+
+        let changes = universe.tick();
+
+        assert_eq!(changes.births(), &[point![0, -1], point![0, 1], point![1, -1], point![1, 1]]);
+        assert_eq!(changes.decays(), &[(point![0, 0], 2), (point![1, 0], 2)]);
+        assert_eq!(changes.deaths(), &[]);
+
+        let changes = universe.tick();
+
+        assert_eq!(changes.decays(), &[(point![0, -1], 2), (point![0, 1], 2), (point![1, -1], 2), (point![1, 1], 2)]);
+        assert_eq!(changes.deaths(), &[point![0, 0], point![1, 0]]);
+        assert_eq!(universe.state(&point![0, 1]), 2);
+    }
+
+    #[test]
+    fn test_tick_larger_than_life() {
+        // Square of 2x2 cells: each cell of the square sees the 3 others within range 2,
+        // dead cells only see 4 alive cells using moore neighborhood
+        let mut universe = Universe::new();
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+        universe.set_alive(point![0, 1]);
+        universe.set_alive(point![1, 1]);
+        universe.set_rule("R2,C0,M0,S3..3,B4..4,NN".parse().unwrap());
+
+        let changes = universe.tick();
+
+        assert_eq!(changes, Changes::new());
+
+        let mut universe = Universe::new();
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+        universe.set_alive(point![0, 1]);
+        universe.set_alive(point![1, 1]);
+        universe.set_rule("R2,C0,M0,S3..3,B4..4,NM".parse().unwrap());
+
+        let changes = universe.tick();
+
+        assert_eq!(changes.deaths(), &[]);
+        assert_eq!(changes.births().len(), 12);
+    }
+
+    #[test]
+    fn test_advance() {
+        let glider = [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]];
+
+        let mut universe = Universe::new();
+        glider.iter().for_each(|&cell| universe.set_alive(cell));
+
+        let mut ticks = universe.clone();
+        let changes = universe.advance(4);
+
+        for _ in 0..4 {
+            ticks.tick();
+        }
+
+        // Glider moved by 1 cell diagonally
+        assert!(universe.cells().iter().eq(ticks.cells().iter()));
+        assert_eq!(changes.births(), &[point![1, 3], point![2, 3], point![3, 2], point![3, 3]]);
+        assert_eq!(changes.deaths(), &[point![0, 2], point![1, 0], point![1, 2], point![2, 2]]);
+
+        // Blinker comes back to its initial state
+        let mut universe = Universe::new();
+        universe.set_alive(point![-1, 0]);
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+
+        assert_eq!(universe.advance(2), Changes::new());
+
+        let changes = universe.clone().tick();
+        assert_eq!(universe.advance(1), changes);
+    }
+
+    #[test]
+    fn test_advance_generations() {
+        let mut universe = Universe::new();
+        universe.set_rule("B2/S/C3".parse().unwrap());
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+
+        let changes = universe.advance(2);
+
+        assert_eq!(changes.births(), &[point![-1, 0], point![0, -2], point![0, 2], point![1, -2], point![1, 2], point![2, 0]]);
+        assert_eq!(changes.decays(), &[(point![0, -1], 2), (point![0, 1], 2), (point![1, -1], 2), (point![1, 1], 2)]);
+        assert_eq!(changes.deaths(), &[point![0, 0], point![1, 0]]);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let mut universe = Universe::new();
+        universe.set_history_capacity(2);
+        universe.set_alive(point![-1, 0]);
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+        universe.set_alive(point![1, 0]);
+
+        assert_eq!(universe.generation(), 0);
+        assert_eq!(universe.population(), 3);
+
+        universe.set_alive(point![5, 5]);
+        universe.tick();
+
+        assert_eq!(universe.generation(), 1);
+        assert_eq!(universe.population(), 3);
+        assert_eq!(universe.births(), 2);
+        assert_eq!(universe.deaths(), 3);
+
+        universe.advance(3);
+
+        assert_eq!(universe.generation(), 4);
+        assert_eq!(universe.history().len(), 2);
+        assert_eq!(
+            universe.history().last(),
+            Some(&Statistics { generation: 4, population: 3, births: 2, deaths: 2 })
+        );
+
+        universe.hashlife_step(3).unwrap();
+
+        assert_eq!(universe.generation(), 12);
+        assert_eq!(universe.population(), 3);
+    }
+
+    #[test]
+    fn test_random_is_reproducible() {
+        let a: Universe = Universe::random(Vector2::new(16, 16), 0.5, &mut Rng::new(7));
+        let b: Universe = Universe::random(Vector2::new(16, 16), 0.5, &mut Rng::new(7));
+
+        assert!(a.cells().iter().eq(b.cells().iter()));
+        assert!(a.cells().iter().count() > 0);
+    }
+
+    #[test]
+    fn test_insert_around_is_reproducible() {
+        let mut a = Universe::new();
+        let mut b = Universe::new();
+
+        let changes = a.insert_around(point![0, 0], 3, 0.25, &mut Rng::new(7));
+
+        assert_eq!(b.insert_around(point![0, 0], 3, 0.25, &mut Rng::new(7)), changes);
+        assert!(changes.births().iter().all(|cell| cell.x * cell.x + cell.y * cell.y <= 9));
+
+        // Cells beyond coordinate limits are skipped
+        let changes = a.insert_around(point![i32::MAX, 0], 3, 1.0, &mut Rng::new(7));
+
+        assert_eq!(changes.births().len(), 18);
+    }
+
+    #[test]
+    fn test_glider_on_torus() {
+        let glider = [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]];
+
+        let mut universe = Universe::new();
+        universe.set_topology(Topology::Torus(Vector2::new(8, 6)));
+        glider.iter().for_each(|&cell| universe.set_alive(cell));
+
+        // Glider moves 1 cell diagonally every 4 generations
+        for _ in 0..4 * 24 {
+            universe.tick();
+        }
+
+        let mut cells = universe.cells().iter().map(|(&pt, _)| pt).collect::<Vec<_>>();
+        cells.sort_by_key(|pt| (pt.x, pt.y));
+
+        assert_eq!(cells, [point![0, 2], point![1, 0], point![1, 2], point![2, 1], point![2, 2]]);
+    }
+
+    #[test]
+    fn test_bounded_kills_outside_cells() {
+        let mut universe = Universe::new();
+        universe.set_alive(point![-1, 0]);
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+
+        universe.set_topology(Topology::Bounded(Vector2::new(4, 4)));
+        universe.set_alive(point![5, 5]);
+
+        assert!(universe.cells().iter().eq([(&point![1, 0], &ALIVE), (&point![0, 0], &ALIVE)]));
+
+        // Block in a corner stays still, blinker cells beyond the border never appear
+        universe.set_alive(point![0, 1]);
+        universe.set_alive(point![1, 1]);
+        universe.tick();
+
+        assert_eq!(universe.cells().iter().count(), 4);
+    }
+
+    #[test]
+    fn test_hashlife_needs_plane() {
+        let mut universe = Universe::new();
+        universe.set_topology(Topology::Torus(Vector2::new(8, 8)));
+
+        assert_eq!(universe.hashlife_step(2), Err("hashlife only supports the unbounded plane topology"));
+    }
+
+    #[test]
+    fn test_hashlife_step() {
+        let glider = [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]];
+
+        let mut hashlife = Universe::new();
+        glider.iter().for_each(|&cell| hashlife.set_alive(cell));
+
+        let mut ticks = hashlife.clone();
+
+        hashlife.hashlife_step(2).unwrap();
+        hashlife.tick();
+
+        for _ in 0..5 {
+            ticks.tick();
+        }
+
+        let mut expected = ticks.cells().iter().map(|(&pt, _)| pt).collect::<Vec<_>>();
+        let mut cells = hashlife.cells().iter().map(|(&pt, _)| pt).collect::<Vec<_>>();
+        expected.sort_by_key(|pt| (pt.x, pt.y));
+        cells.sort_by_key(|pt| (pt.x, pt.y));
+
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn test_blinker_at_limits() {
+        let mut universe = Universe::new();
+        universe.set_alive(point![i32::MAX, -1]);
+        universe.set_alive(point![i32::MAX, 0]);
+        universe.set_alive(point![i32::MAX, 1]);
+
+        // Cells beyond the limit are a dead border
+        let changes = universe.tick();
+
+        assert_eq!(changes.births(), &[point![i32::MAX - 1, 0]]);
+        assert_eq!(changes.deaths(), &[point![i32::MAX, -1], point![i32::MAX, 1]]);
+
+        universe.tick();
+
+        assert_eq!(universe.population(), 0);
+    }
+
+    #[test]
+    fn test_glider_with_64_bits_coordinates() {
+        let origin = point![1i64 << 40, -(1i64 << 40)];
+        let glider = [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]];
+
+        let mut universe = Universe::<i64>::new();
+        glider.iter().for_each(|cell| universe.set_alive(origin + cell.coords.cast()));
+
+        universe.advance(4);
+
+        // Glider moved by 1 cell diagonally
+        let mut cells = universe.cells().iter().map(|(pt, _)| pt - origin).collect::<Vec<_>>();
+        cells.sort_by_key(|pt| (pt.x, pt.y));
+
+        assert_eq!(cells, [Vector2::new(1, 3), Vector2::new(2, 1), Vector2::new(2, 3), Vector2::new(3, 2), Vector2::new(3, 3)]);
+    }
+
+    #[test]
+    fn test_translate() {
+        let glider = [point![1, 0], point![2, 1], point![0, 2], point![1, 2], point![2, 2]];
+
+        let mut universe = Universe::new();
+        glider.iter().for_each(|&cell| universe.set_alive(cell));
+
+        let mut expected = universe.clone();
+        expected.advance(4);
+
+        // Glider moved by 1 cell diagonally
+        universe.translate(&Vector2::new(1, 1));
+        assert_eq!(universe.cells(), expected.cells());
+
+        // Cells beyond the limits die
+        universe.translate(&Vector2::new(i32::MAX - 2, 0));
+        assert_eq!(universe.population(), 3);
+    }
+
+    #[test]
+    fn test_clear_area() {
+        let mut universe = Universe::new();
+        universe.set_alive(point![-1, 0]);
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+        universe.set_alive(point![5, 5]);
+        universe.set_alive(point![5, 6]);
+        universe.set_alive(point![6, 5]);
+        universe.set_alive(point![6, 6]);
+
+        universe.clear_area(&(point![-1, -1]..=point![1, 1]));
+
+        assert_eq!(universe.population(), 4);
+        assert_eq!(universe.tick(), Changes::new());
+    }
+
+    #[test]
+    fn test_transform_area() {
+        let mut universe = Universe::new();
+        universe.set_alive(point![0, 0]);
+        universe.set_alive(point![1, 0]);
+        universe.set_alive(point![2, 0]);
+        universe.set_alive(point![2, 1]);
+        universe.set_alive(point![5, 5]);
+
+        universe.transform_area(&(point![0, 0]..=point![2, 1]), Symmetry::Rotate90).unwrap();
+
+        let mut cells = universe.cells().iter().map(|(&pt, _)| pt).collect::<Vec<_>>();
+        cells.sort_by_key(|pt| (pt.x, pt.y));
+
+        assert_eq!(cells, [point![0, 2], point![1, 0], point![1, 1], point![1, 2], point![5, 5]]);
+        assert_eq!(universe.population(), 5);
+
+        // Whole universe
+        let initial = universe.cells().clone();
+
+        universe.transform(Symmetry::Rotate180);
+        assert!(universe.is_alive(&point![-6, -6]));
+
+        universe.transform(Symmetry::Rotate180);
+        assert_eq!(universe.cells(), &initial);
+
+        // Area too large
+        assert_eq!(
+            universe.transform_area(&(point![i32::MIN, 0]..=point![i32::MAX, 0]), Symmetry::FlipHorizontal),
+            Err("area is too large to be transformed")
+        );
+
+        // Area at coordinate limits, mapped far from its first corner
+        let mut universe = Universe::new();
+        universe.set_alive(point![i32::MAX - 1, 0]);
+
+        universe.transform_area(&(point![i32::MAX - 1, 0]..=point![i32::MAX, 0]), Symmetry::FlipHorizontal).unwrap();
+        assert!(universe.is_alive(&point![i32::MAX, 0]));
+        assert_eq!(universe.population(), 1);
+    }
+
+    #[test]
+    fn test_rle() {
+        let mut universe = Universe::<i32>::from_rle("#N Blinker\nx = 3, y = 1, rule = B3/S23:T8,8\n3o!").unwrap();
+
+        assert_eq!(universe.population(), 3);
+        assert_eq!(universe.topology(), Topology::Torus(Vector2::new(8, 8)));
+
+        universe.tick();
+
+        assert_eq!(universe.to_rle(), "#R 1 0\nx = 1, y = 8, rule = B3/S23:T8,8\no$o6$o!\n");
+        assert_eq!(
+            Universe::<i32>::from_rle("x = 3, y = 1\n3o$?").err(),
+            Some(ParseError::new(2, 4, "unexpected character in pattern"))
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut universe = Universe::<i32>::from_rle("x = 3, y = 3, rule = B2/S345/C4:T40,30
+2o$obo$b2o!").unwrap();
+        universe.set_update_area((Included(point![0, 0]), Excluded(point![20, 20])));
+        universe.advance(5);
+
+        let mut restored = Universe::<i32>::deserialize(&universe.serialize()).unwrap();
+
+        assert_eq!(restored.cells(), universe.cells());
+        assert_eq!(restored.rule(), universe.rule());
+        assert_eq!(restored.topology(), universe.topology());
+        assert_eq!(restored.update_area(), universe.update_area());
+        assert_eq!(restored.generation(), 5);
+        assert_eq!(restored.population(), universe.population());
+
+        // Restored universe evolves the same way
+        universe.advance(10);
+        restored.advance(10);
+
+        assert_eq!(restored.cells(), universe.cells());
+
+        // Unknown sections are skipped, missing ones keep default values
+        let mut snapshot = universe.snapshot();
+        snapshot.set_section(200, Encoder::new());
+
+        assert!(Universe::<i32>::from_snapshot(&snapshot).is_ok());
+        assert_eq!(Universe::<i32>::from_snapshot(&Snapshot::new()).unwrap().rule(), Rule::conway());
+
+        // Coordinates limits
+        let mut far: Universe<i64> = Universe::new();
+        far.set_alive(point![1 << 40, 0]);
+
+        assert_eq!(Universe::<i64>::deserialize(&far.serialize()).unwrap().cells(), far.cells());
+        assert_eq!(Universe::<i32>::deserialize(&far.serialize()).err(), Some("snapshot exceeds coordinate limits"));
+    }
+
+    #[test]
+    fn test_render_png() {
+        let universe = Universe::<i32>::from_rle("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        let png = universe.render_png(&(point![0, 0]..=point![3, 2]), 2, &Palette::light()).unwrap();
+
+        // 8x6 pixels glider, with an empty column
+        assert_eq!(png, [
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x08,
+            0x00, 0x00, 0x00, 0x06, 0x08, 0x03, 0x00, 0x00, 0x00, 0xc9, 0xdb, 0x2f, 0xc9, 0x00, 0x00, 0x00, 0x06, 0x50, 0x4c, 0x54,
+            0x45, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x55, 0xc2, 0xd3, 0x7e, 0x00, 0x00, 0x00, 0x1b, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0x01, 0x63, 0x60, 0x60, 0x60, 0x64, 0x64, 0x80, 0x00, 0x38, 0x83, 0x81, 0x81, 0x91, 0x11, 0x99, 0xc1, 0x08, 0x06, 0x48,
+            0x0c, 0x00, 0x01, 0xce, 0x00, 0x15, 0xc4, 0x2e, 0x2e, 0x42, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42,
+            0x60, 0x82,
+        ]);
+
+        assert_eq!(universe.render_png(&(point![0, 0]..=point![-1, 2]), 2, &Palette::light()), Err("cannot render an empty image"));
+        assert_eq!(universe.render_png(&(point![0, 0]..=point![1 << 20, 2]), 100, &Palette::light()), Err("image is too large to be rendered"));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::hash_set::{HashSet, Iter};
+use std::mem;
+use std::ops::Bound::{self, Unbounded};
+use na::Point2;
+use py::Holds;
+use crate::coordinate::Coordinate;
+use crate::neighborhood::Neighborhood;
+use crate::topology::Topology;
+
+/// Manages cells to update
+#[derive(Clone, Debug)]
+pub struct UpdateList<C: Coordinate = i32> {
+    area: UpdateArea<C>,
+    cells: HashSet<Point2<C>>,
+}
+
+// Types
+pub type UpdateArea<C = i32> = (Bound<Point2<C>>, Bound<Point2<C>>);
+
+// Methods
+impl<C: Coordinate> UpdateList<C> {
+    /// Creates an empty update list
+    #[inline]
+    pub fn new() -> UpdateList<C> {
+        UpdateList {
+            area: (Unbounded, Unbounded),
+            cells: HashSet::new(),
+        }
+    }
+
+    /// Creates an empty update list
+    #[inline]
+    pub fn inside(area: UpdateArea<C>) -> UpdateList<C> {
+        UpdateList {
+            area,
+            cells: HashSet::new(),
+        }
+    }
+
+    /// Return current area limit
+    #[inline]
+    pub fn area(&self) -> &UpdateArea<C> {
+        &self.area
+    }
+
+    /// Changes update area and return current one
+    #[inline]
+    pub fn change_area(&mut self, area: UpdateArea<C>) -> UpdateArea<C> {
+        mem::replace(&mut self.area, area)
+    }
+
+    /// Iterates on registered cells, in no particular order
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, Point2<C>> {
+        self.cells.iter()
+    }
+
+    /// Register given point
+    #[inline]
+    pub fn register(&mut self, cell: Point2<C>) {
+        self.cells.insert(cell);
+    }
+
+    /// Register given point and neighbors, as mapped by topology
+    pub fn register_with_neighbors(&mut self, cell: Point2<C>, topology: &Topology, neighborhood: &Neighborhood) {
+        if !self.area.holds(&cell) {
+            return;
+        }
+
+        for cell in topology.around(cell, neighborhood) {
+            self.register(cell);
+        }
+    }
+}
+
+// Utils
+impl<C: Coordinate> Default for UpdateList<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
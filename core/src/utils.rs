@@ -0,0 +1,52 @@
+use std::cmp;
+use na::Point2;
+use crate::coordinate::Coordinate;
+use crate::quadtree::quarter::global_quarter;
+
+pub fn cmp_xy_order<C: Coordinate>(lhs: &Point2<C>, rhs: &Point2<C>) -> cmp::Ordering {
+    lhs.iter().cmp(rhs.iter())
+}
+
+/// Z-order (morton order) of points, following quarter indexes of quadtree nodes
+pub fn cmp_z_order<C: Coordinate>(lhs: &Point2<C>, rhs: &Point2<C>) -> cmp::Ordering {
+    let quarters = (global_quarter(lhs) as u8).cmp(&(global_quarter(rhs) as u8));
+
+    if quarters.is_ne() {
+        return quarters;
+    }
+
+    // Axis with the highest differing bit decides, x being first on a tie
+    let (dx, dy) = ((lhs.x ^ rhs.x).to_bits(), (lhs.y ^ rhs.y).to_bits());
+
+    if dx < dy && dx < (dx ^ dy) {
+        lhs.y.cmp(&rhs.y)
+    } else {
+        lhs.x.cmp(&rhs.x)
+    }
+}
+
+/// CRC-32 lookup table (IEEE polynomial, as used by zip and png)
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+
+    while n < 256 {
+        let mut crc = n as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xedb88320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+
+        table[n] = crc;
+        n += 1;
+    }
+
+    table
+};
+
+/// CRC-32 checksum of given bytes
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
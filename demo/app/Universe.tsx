@@ -72,7 +72,7 @@ export default function Universe() {
       if (context && time - lastTick > TICK_RATE) {
         lastTick = time;
 
-        measure("tick", () => universe.tick(context));
+        measure("tick", () => universe.draw(context, universe.tick()));
       }
 
       frame = requestAnimationFrame(tick);
@@ -90,7 +90,7 @@ export default function Universe() {
     const now = performance.now();
 
     if (context && now - last.current > 10) {
-      universe.insert_around(context, new PointInt2D(event.clientX / 5, event.clientY / 5), 3);
+      universe.draw(context, universe.insert_around(new PointInt2D(event.clientX / 5, event.clientY / 5), 3, undefined));
       last.current = now;
     }
   }, [context, universe]);
@@ -101,7 +101,7 @@ export default function Universe() {
     if (context && now - last.current > 10) {
       for (let idx = 0; idx < event.changedTouches.length; idx++) {
         const touch = event.changedTouches[idx];
-        universe.insert_around(context, new PointInt2D(touch.clientX / 5, touch.clientY / 5), 3);
+        universe.draw(context, universe.insert_around(new PointInt2D(touch.clientX / 5, touch.clientY / 5), 3, undefined));
       }
       last.current = now;
     }
@@ -0,0 +1,36 @@
+use std::ops::RangeInclusive;
+use na::Point2;
+use py::wasm::PointInt2D;
+use wasm_bindgen::prelude::*;
+
+/// Smallest area holding all cells of a universe
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[wasm_bindgen]
+pub struct BoundingBox {
+    first: Point2<i32>,
+    last: Point2<i32>,
+}
+
+#[wasm_bindgen]
+impl BoundingBox {
+    /// First cell of the area (top left corner)
+    #[wasm_bindgen(getter)]
+    pub fn first(&self) -> PointInt2D {
+        PointInt2D::from(self.first)
+    }
+
+    /// Last cell of the area (bottom right corner), included in the area
+    #[wasm_bindgen(getter)]
+    pub fn last(&self) -> PointInt2D {
+        PointInt2D::from(self.last)
+    }
+}
+
+// Conversions
+impl From<RangeInclusive<Point2<i32>>> for BoundingBox {
+    #[inline]
+    fn from(value: RangeInclusive<Point2<i32>>) -> Self {
+        let (first, last) = value.into_inner();
+        BoundingBox { first, last }
+    }
+}
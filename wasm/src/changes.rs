@@ -0,0 +1,41 @@
+use py::wasm::PointInt2D;
+use wasm_bindgen::prelude::*;
+
+/// Cells born, dying and dead during an update of the universe
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[wasm_bindgen]
+pub struct Changes(gol::Changes);
+
+#[wasm_bindgen]
+impl Changes {
+    #[wasm_bindgen(getter)]
+    pub fn births(&self) -> Vec<PointInt2D> {
+        self.0.births().iter().map(|&cell| PointInt2D::from(cell)).collect()
+    }
+
+    /// Cells entering a dying state
+    #[wasm_bindgen(getter)]
+    pub fn decays(&self) -> Vec<PointInt2D> {
+        self.0.decays().iter().map(|&(cell, _)| PointInt2D::from(cell)).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn deaths(&self) -> Vec<PointInt2D> {
+        self.0.deaths().iter().map(|&cell| PointInt2D::from(cell)).collect()
+    }
+}
+
+// Conversions
+impl AsRef<gol::Changes> for Changes {
+    #[inline]
+    fn as_ref(&self) -> &gol::Changes {
+        &self.0
+    }
+}
+
+impl From<gol::Changes> for Changes {
+    #[inline]
+    fn from(value: gol::Changes) -> Self {
+        Changes(value)
+    }
+}
@@ -1,8 +1,13 @@
+extern crate game_of_life_core as gol;
 extern crate nalgebra as na;
 extern crate pythagore as py;
 
-mod quadtree;
+mod bounding_box;
+mod changes;
+mod rule;
+mod statistics;
+mod symmetry;
+mod topology;
 mod universe;
 mod universe_style;
-mod update_list;
 mod utils;
@@ -0,0 +1,41 @@
+use wasm_bindgen::prelude::*;
+
+/// Life-like rule (B/S notation), with optional dying states (Generations rules),
+/// extended neighborhoods (Larger than Life rules) or non-totalistic counts (Hensel notation and MAP rules)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[wasm_bindgen]
+pub struct Rule(gol::Rule);
+
+#[wasm_bindgen]
+impl Rule {
+    /// Conway's game of life rule (B3/S23)
+    pub fn conway() -> Rule {
+        Rule(gol::Rule::conway())
+    }
+
+    /// Parses a rulestring (B3/S23, B2-a/S12 or legacy 23/3 form, with an optional /C3 or /3 states count,
+    /// MAP rules, or larger than life form like R5,C0,M1,S34..58,B34..45,NM)
+    pub fn parse(rule: &str) -> Result<Rule, JsError> {
+        rule.parse().map(Rule).map_err(JsError::new)
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn js_to_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+// Conversions
+impl From<gol::Rule> for Rule {
+    #[inline]
+    fn from(value: gol::Rule) -> Self {
+        Rule(value)
+    }
+}
+
+impl From<Rule> for gol::Rule {
+    #[inline]
+    fn from(value: Rule) -> Self {
+        value.0
+    }
+}
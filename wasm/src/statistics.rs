@@ -0,0 +1,37 @@
+use wasm_bindgen::prelude::*;
+
+/// Statistics of the universe after an update
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[wasm_bindgen]
+pub struct Statistics(gol::Statistics);
+
+#[wasm_bindgen]
+impl Statistics {
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u64 {
+        self.0.generation
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> usize {
+        self.0.population
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn births(&self) -> usize {
+        self.0.births
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn deaths(&self) -> usize {
+        self.0.deaths
+    }
+}
+
+// Conversions
+impl From<gol::Statistics> for Statistics {
+    #[inline]
+    fn from(value: gol::Statistics) -> Self {
+        Statistics(value)
+    }
+}
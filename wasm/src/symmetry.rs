@@ -0,0 +1,37 @@
+use wasm_bindgen::prelude::*;
+
+/// Rotations and flips of the plane
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[wasm_bindgen]
+pub enum Symmetry {
+    /// Quarter turn, from x axis to y axis
+    Rotate90,
+    /// Half turn
+    Rotate180,
+    /// Quarter turn, from y axis to x axis
+    Rotate270,
+    /// Mirrors x axis
+    FlipHorizontal,
+    /// Mirrors y axis
+    FlipVertical,
+    /// Swaps x and y axis
+    FlipDiagonal,
+    /// Swaps and mirrors x and y axis
+    FlipAntiDiagonal,
+}
+
+// Conversions
+impl From<Symmetry> for gol::Symmetry {
+    #[inline]
+    fn from(value: Symmetry) -> Self {
+        match value {
+            Symmetry::Rotate90 => gol::Symmetry::Rotate90,
+            Symmetry::Rotate180 => gol::Symmetry::Rotate180,
+            Symmetry::Rotate270 => gol::Symmetry::Rotate270,
+            Symmetry::FlipHorizontal => gol::Symmetry::FlipHorizontal,
+            Symmetry::FlipVertical => gol::Symmetry::FlipVertical,
+            Symmetry::FlipDiagonal => gol::Symmetry::FlipDiagonal,
+            Symmetry::FlipAntiDiagonal => gol::Symmetry::FlipAntiDiagonal,
+        }
+    }
+}
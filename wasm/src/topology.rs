@@ -0,0 +1,65 @@
+use py::wasm::VectorInt2D;
+use wasm_bindgen::prelude::*;
+
+/// Shape of the universe space
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[wasm_bindgen]
+pub struct Topology(gol::Topology);
+
+#[wasm_bindgen]
+impl Topology {
+    /// Unbounded plane
+    pub fn plane() -> Topology {
+        Topology(gol::Topology::Plane)
+    }
+
+    /// Finite plane, surrounded by dead cells
+    pub fn bounded(size: VectorInt2D) -> Result<Topology, JsError> {
+        gol::Topology::bounded(*size.as_ref()).map(Topology).map_err(JsError::new)
+    }
+
+    /// Wraps both axis
+    pub fn torus(size: VectorInt2D) -> Result<Topology, JsError> {
+        gol::Topology::torus(*size.as_ref()).map(Topology).map_err(JsError::new)
+    }
+
+    /// Wraps x axis, unbounded on y axis
+    pub fn cylinder(width: i32) -> Result<Topology, JsError> {
+        gol::Topology::cylinder(width).map(Topology).map_err(JsError::new)
+    }
+
+    /// Wraps both axis, flipping x when wrapping on y axis
+    pub fn klein_bottle(size: VectorInt2D) -> Result<Topology, JsError> {
+        gol::Topology::klein_bottle(*size.as_ref()).map(Topology).map_err(JsError::new)
+    }
+
+    /// Wraps both axis, flipping the other axis when wrapping
+    pub fn cross_surface(size: VectorInt2D) -> Result<Topology, JsError> {
+        gol::Topology::cross_surface(*size.as_ref()).map(Topology).map_err(JsError::new)
+    }
+
+    /// Parses golly's topology notation (like T100,80)
+    pub fn parse(topology: &str) -> Result<Topology, JsError> {
+        topology.parse().map(Topology).map_err(JsError::new)
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn js_to_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+// Conversions
+impl From<gol::Topology> for Topology {
+    #[inline]
+    fn from(value: gol::Topology) -> Self {
+        Topology(value)
+    }
+}
+
+impl From<Topology> for gol::Topology {
+    #[inline]
+    fn from(value: Topology) -> Self {
+        value.0
+    }
+}
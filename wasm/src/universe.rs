@@ -1,22 +1,29 @@
-use std::mem;
 use std::ops::Bound::{Excluded, Included};
-use js_sys::Math;
-use na::{distance, point, Point2};
-use py::{Holds, Walkable};
+use gol::snapshot::{Encoder, Snapshot, APPLICATION_SECTIONS};
+use gol::Rng;
+use js_sys::Uint8Array;
+use na::{point, Point2};
 use py::wasm::{PointInt2D, VectorInt2D};
 use wasm_bindgen::prelude::*;
 use web_sys::CanvasRenderingContext2d;
-use crate::quadtree::GlobalQuadtree;
+use crate::bounding_box::BoundingBox;
+use crate::changes::Changes;
+use crate::rule::Rule;
+use crate::statistics::Statistics;
+use crate::symmetry::Symmetry;
+use crate::topology::Topology;
 use crate::universe_style::UniverseStyle;
-use crate::update_list::UpdateList;
+use crate::utils::{parse_error, random_seed};
+
+/// Snapshot section holding universe style
+const STYLE_SECTION: u8 = APPLICATION_SECTIONS;
 
 /// Life universe
 #[derive(Clone)]
 #[wasm_bindgen]
 pub struct Universe {
-    cells: GlobalQuadtree,
+    universe: gol::Universe,
     style: UniverseStyle,
-    updates: UpdateList,
 }
 
 #[wasm_bindgen]
@@ -24,9 +31,8 @@ impl Universe {
     /// Builds a dead universe
     pub fn dead() -> Universe {
         Universe {
-            cells: GlobalQuadtree::new(),
+            universe: gol::Universe::new(),
             style: UniverseStyle::default(),
-            updates: UpdateList::new(),
         }
     }
 
@@ -39,7 +45,7 @@ impl Universe {
                 let i = row * size.dx() + col;
 
                 if i % 2 == 0 || i % 7 == 0 || i % 13 == 0 {
-                    universe.set_alive(point![col, row])
+                    universe.universe.set_alive(point![col, row])
                 }
             }
         }
@@ -49,102 +55,236 @@ impl Universe {
 
     /// Builds a random universe
     pub fn random(size: VectorInt2D) -> Universe {
-        let mut universe = Universe::dead();
-
-        for row in 0..size.dy() {
-            for col in 0..size.dx() {
-                let rand = Math::random();
+        Universe::random_seeded(size, random_seed(), 0.5)
+    }
 
-                if rand < 0.5 {
-                    universe.set_alive(point![col, row])
-                }
-            }
+    /// Builds a random universe, reproducible using given seed
+    pub fn random_seeded(size: VectorInt2D, seed: u64, density: f64) -> Universe {
+        Universe {
+            universe: gol::Universe::random(*size.as_ref(), density, &mut Rng::new(seed)),
+            style: UniverseStyle::default(),
         }
-
-        universe
     }
 
-    /// Inserts some cells around given position
-    pub fn insert_around(&mut self, ctx: &CanvasRenderingContext2d, center: &PointInt2D, r: i32) {
-        let center = center.as_ref();
-        let area = point![center.x - r, center.y - r]..=point![center.x + r, center.y + r];
+    /// Parses a run length encoded pattern.
+    /// Parse errors are thrown with their line and column.
+    pub fn from_rle(input: &str) -> Result<Universe, JsValue> {
+        let universe = gol::Universe::from_rle(input).map_err(parse_error)?;
+
+        Ok(Universe {
+            universe,
+            style: UniverseStyle::default(),
+        })
+    }
 
-        area.walk().unwrap().iter()
-            .filter(|cell| distance::<f64, 2>(&center.cast(), &cell.cast()) <= r as f64)
-            .for_each(|cell| {
-                let rand = Math::random();
+    /// Exports cells, rule and topology as a run length encoded pattern
+    pub fn to_rle(&self) -> String {
+        self.universe.to_rle()
+    }
 
-                if rand < 0.25 {
-                    self.set_alive(cell);
+    /// Saves universe and its style as a compact binary snapshot
+    pub fn serialize(&self) -> Uint8Array {
+        let mut snapshot = self.universe.snapshot();
 
-                    let pos = cell.cast::<f64>() * 5.0;
+        let mut style = Encoder::new();
+        self.style.encode(&mut style);
+        snapshot.set_section(STYLE_SECTION, style);
 
-                    ctx.set_fill_style(self.style.alive_color());
-                    ctx.fill_rect(pos.x, pos.y, 5.0, 5.0);
-                }
-            });
+        Uint8Array::from(&snapshot.to_bytes()[..])
     }
 
-    /// Compute next state
-    pub fn tick(&mut self, ctx: &CanvasRenderingContext2d) {
-        let updates = UpdateList::inside(*self.updates.area());
-        let old = Universe {
-            cells: self.cells.clone(),
-            style: self.style.clone(),
-            updates: mem::replace(&mut self.updates, updates),
+    /// Restores universe and its style from a binary snapshot.
+    /// Snapshots written by older versions are supported, default style being used if missing.
+    pub fn deserialize(bytes: &[u8]) -> Result<Universe, JsError> {
+        let snapshot = Snapshot::from_bytes(bytes).map_err(JsError::new)?;
+
+        let style = match snapshot.section(STYLE_SECTION) {
+            Some(mut section) => UniverseStyle::decode(&mut section).map_err(JsError::new)?,
+            None => UniverseStyle::default(),
         };
 
-        for &cell in old.updates.iter() {
-            let (is_alive, neighbors) = old.cell_state(&cell);
+        Ok(Universe {
+            universe: gol::Universe::from_snapshot(&snapshot).map_err(JsError::new)?,
+            style,
+        })
+    }
 
-            if is_alive {
-                if !(2..=3).contains(&neighbors) {
-                    self.set_dead(cell);
+    /// Inserts some cells around given position, returning inserted cells.
+    /// Inserted cells are reproducible if a seed is given.
+    pub fn insert_around(&mut self, center: &PointInt2D, r: i32, seed: Option<u64>) -> Changes {
+        let mut rng = Rng::new(seed.unwrap_or_else(random_seed));
+        Changes::from(self.universe.insert_around(*center.as_ref(), r, 0.25, &mut rng))
+    }
 
-                    let pos = cell.cast::<f64>() * 5.0;
+    /// Compute next state, returning changed cells
+    pub fn tick(&mut self) -> Changes {
+        Changes::from(self.universe.tick())
+    }
 
-                    ctx.set_fill_style(self.style.dead_color());
-                    ctx.fill_rect(pos.x, pos.y, 5.0, 5.0);
-                }
-            } else if neighbors == 3 {
-                self.set_alive(cell);
+    /// Computes given number of generations without drawing, returning net changed cells
+    pub fn advance(&mut self, generations: u32) -> Changes {
+        Changes::from(self.universe.advance(generations))
+    }
 
-                let pos = cell.cast::<f64>() * 5.0;
+    /// Advances universe by 2^step generations at once, using hashlife.
+    /// Whole universe evolves, regardless of the update area.
+    pub fn hashlife_step(&mut self, step: u32) -> Result<(), JsError> {
+        self.universe.hashlife_step(step).map_err(JsError::new)
+    }
 
-                ctx.set_fill_style(self.style.alive_color());
-                ctx.fill_rect(pos.x, pos.y, 5.0, 5.0);
-            }
+    /// Compacts cells storage, releasing memory kept from dead cells.
+    /// Useful after long runs, or when a large pattern died.
+    pub fn shrink(&mut self) {
+        self.universe.shrink();
+    }
+
+    /// Moves all cells by given offset. Cells moved beyond coordinate limits die.
+    pub fn translate(&mut self, offset: VectorInt2D) {
+        self.universe.translate(offset.as_ref());
+    }
+
+    /// Rotates or flips all cells, around the corner shared by the 4 cells surrounding origin
+    pub fn transform(&mut self, symmetry: Symmetry) {
+        self.universe.transform(symmetry.into());
+    }
+
+    /// Moves cells between given points (included) by given offset, replacing cells at their destination
+    pub fn translate_area(&mut self, start: &PointInt2D, end: &PointInt2D, offset: VectorInt2D) {
+        self.universe.translate_area(&(*start.as_ref()..=*end.as_ref()), offset.as_ref());
+    }
+
+    /// Rotates or flips cells between given points (included), replacing cells at their destination.
+    /// Transformed area keeps the same first corner.
+    pub fn transform_area(&mut self, start: &PointInt2D, end: &PointInt2D, symmetry: Symmetry) -> Result<(), JsError> {
+        self.universe.transform_area(&(*start.as_ref()..=*end.as_ref()), symmetry.into())
+            .map_err(JsError::new)
+    }
+
+    /// Draws given changes
+    pub fn draw(&self, ctx: &CanvasRenderingContext2d, changes: &Changes) {
+        ctx.set_fill_style_str(self.style.dead_color());
+
+        for cell in changes.as_ref().deaths() {
+            self.draw_cell(ctx, cell);
+        }
+
+        for &(cell, state) in changes.as_ref().decays() {
+            ctx.set_fill_style_str(self.style.state_color(state));
+            self.draw_cell(ctx, &cell);
+        }
+
+        ctx.set_fill_style_str(self.style.alive_color());
+
+        for cell in changes.as_ref().births() {
+            self.draw_cell(ctx, cell);
         }
     }
 
+    /// Draws all cells visible in a canvas of given size
     pub fn redraw(&self, ctx: &CanvasRenderingContext2d, size: VectorInt2D) {
-        ctx.set_fill_style(self.style.dead_color());
+        ctx.set_fill_style_str(self.style.dead_color());
         ctx.fill_rect(0.0, 0.0, size.dx() as f64, size.dy() as f64);
 
-        let area = Point2::origin()..point![size.dx(), size.dy()];
+        let cell_size = self.style.cell_size();
+        let end = point![
+            (size.dx() as f64 / cell_size).ceil() as i32,
+            (size.dy() as f64 / cell_size).ceil() as i32
+        ];
 
-        for cell in self.cells.query(&area) {
-            let pos = cell.cast::<f64>() * 5.0;
+        let mut fill_state = None;
 
-            ctx.set_fill_style(self.style.alive_color());
-            ctx.fill_rect(pos.x, pos.y, 5.0, 5.0);
+        for (cell, &state) in self.universe.cells().query(&(Point2::origin()..end)) {
+            if fill_state != Some(state) {
+                ctx.set_fill_style_str(self.style.state_color(state));
+                fill_state = Some(state);
+            }
+
+            self.draw_cell(ctx, cell);
         }
     }
 
+    /// Renders cells between given points (included) as a png image, using given style colors.
+    /// Works without any canvas, each cell being a square of cell_size pixels.
+    pub fn render_png(&self, start: &PointInt2D, end: &PointInt2D, cell_size: u32, style: &UniverseStyle) -> Result<Uint8Array, JsError> {
+        let palette = style.palette().map_err(JsError::new)?;
+        let png = self.universe.render_png(&(*start.as_ref()..=*end.as_ref()), cell_size, &palette)
+            .map_err(JsError::new)?;
+
+        Ok(Uint8Array::from(&png[..]))
+    }
+
     pub fn set_update_area(&mut self, start: &PointInt2D, end: &PointInt2D) {
         let start = *start.as_ref();
         let end = *end.as_ref();
 
-        let old = self.updates.change_area((Included(start), Excluded(end)));
+        self.universe.set_update_area((Included(start), Excluded(end)));
+    }
 
-        for cell in self.cells.iter() {
-            if !old.holds(cell) {
-                let area = point![cell.x - 1, cell.y - 1]..=point![cell.x + 1, cell.y + 1];
+    /// Smallest area holding all cells, undefined if universe is empty
+    #[wasm_bindgen(getter)]
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.universe.bounding_box().map(BoundingBox::from)
+    }
 
-                area.walk().unwrap().iter()
-                    .for_each(|pt| self.updates.register(pt));
-            };
-        }
+    /// Number of generations computed since creation
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u64 {
+        self.universe.generation()
+    }
+
+    /// Number of alive cells
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> usize {
+        self.universe.population()
+    }
+
+    /// Number of cells born during last generation
+    #[wasm_bindgen(getter)]
+    pub fn births(&self) -> usize {
+        self.universe.births()
+    }
+
+    /// Number of cells dead during last generation
+    #[wasm_bindgen(getter)]
+    pub fn deaths(&self) -> usize {
+        self.universe.deaths()
+    }
+
+    /// Statistics of last computed generations, from oldest to most recent
+    #[wasm_bindgen(getter)]
+    pub fn history(&self) -> Vec<Statistics> {
+        self.universe.history().iter().map(|&stats| Statistics::from(stats)).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn history_capacity(&self) -> usize {
+        self.universe.history().capacity()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.universe.set_history_capacity(capacity)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rule(&self) -> Rule {
+        Rule::from(self.universe.rule())
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.universe.set_rule(rule.into())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn topology(&self) -> Topology {
+        Topology::from(self.universe.topology())
+    }
+
+    /// Changes universe topology, killing cells outside of the new space
+    #[wasm_bindgen(setter)]
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.universe.set_topology(topology.into())
     }
 
     #[wasm_bindgen(getter)]
@@ -159,32 +299,11 @@ impl Universe {
 }
 
 impl Universe {
-    /// Set cell at given point alive
-    fn set_alive(&mut self, point: Point2<i32>) {
-        self.cells.insert(point);
-        self.updates.register_with_neighbors(point);
-    }
-
-    /// Set cell at given point dead
-    fn set_dead(&mut self, point: Point2<i32>) {
-        self.cells.remove(&point);
-        self.updates.register_with_neighbors(point);
-    }
-
-    /// Get cell state and neighbor count
-    fn cell_state(&self, point: &Point2<i32>) -> (bool, usize) {
-        let area = point![point.x - 1, point.y - 1]..point![point.x + 2, point.y + 2];
-        let mut neighbors = 0;
-        let mut is_alive = false;
-
-        for pt in self.cells.query(&area) {
-            if pt == point {
-                is_alive = true;
-            } else {
-                neighbors += 1;
-            }
-        }
+    /// Draws a cell, using current fill style
+    fn draw_cell(&self, ctx: &CanvasRenderingContext2d, cell: &Point2<i32>) {
+        let cell_size = self.style.cell_size();
+        let pos = cell.cast::<f64>() * cell_size;
 
-        (is_alive, neighbors)
+        ctx.fill_rect(pos.x, pos.y, cell_size, cell_size);
     }
 }
@@ -1,3 +1,6 @@
+use gol::render::parse_color;
+use gol::snapshot::{Decoder, Encoder};
+use gol::Palette;
 use wasm_bindgen::prelude::*;
 
 /// Universe style
@@ -5,8 +8,9 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 pub struct UniverseStyle {
     cell_size: f64,
-    alive_color: JsValue,
-    dead_color: JsValue,
+    alive_color: String,
+    dead_color: String,
+    dying_colors: Vec<String>,
 }
 
 #[wasm_bindgen]
@@ -15,8 +19,9 @@ impl UniverseStyle {
     pub fn light() -> UniverseStyle {
         UniverseStyle {
             cell_size: 5.0,
-            alive_color: JsValue::from_str("#000000"),
-            dead_color: JsValue::from_str("#ffffff"),
+            alive_color: String::from("#000000"),
+            dead_color: String::from("#ffffff"),
+            dying_colors: vec![String::from("#555555"), String::from("#aaaaaa")],
         }
     }
 
@@ -24,8 +29,9 @@ impl UniverseStyle {
     pub fn dark() -> UniverseStyle {
         UniverseStyle {
             cell_size: 5.0,
-            alive_color: JsValue::from_str("#ffffff"),
-            dead_color: JsValue::from_str("#000000"),
+            alive_color: String::from("#ffffff"),
+            dead_color: String::from("#000000"),
+            dying_colors: vec![String::from("#aaaaaa"), String::from("#555555")],
         }
     }
 
@@ -36,12 +42,23 @@ impl UniverseStyle {
 
     #[wasm_bindgen(getter = alive_color)]
     pub fn js_alive_color(&self) -> JsValue {
-        self.alive_color.clone()
+        JsValue::from_str(&self.alive_color)
     }
 
     #[wasm_bindgen(getter = dead_color)]
     pub fn js_dead_color(&self) -> JsValue {
-        self.dead_color.clone()
+        JsValue::from_str(&self.dead_color)
+    }
+
+    /// Colors of dying states, the last one is used for all following states
+    #[wasm_bindgen(getter = dying_colors)]
+    pub fn js_dying_colors(&self) -> Vec<String> {
+        self.dying_colors.clone()
+    }
+
+    #[wasm_bindgen(setter = dying_colors)]
+    pub fn js_set_dying_colors(&mut self, colors: Vec<String>) {
+        self.dying_colors = colors;
     }
 }
 
@@ -50,13 +67,64 @@ impl UniverseStyle {
         self.cell_size
     }
 
-    pub fn alive_color(&self) -> &JsValue {
+    pub fn alive_color(&self) -> &str {
         &self.alive_color
     }
 
-    pub fn dead_color(&self) -> &JsValue {
+    pub fn dead_color(&self) -> &str {
         &self.dead_color
     }
+
+    /// Colors of cell states, parsed from css colors
+    pub fn palette(&self) -> Result<Palette, &'static str> {
+        Ok(Palette {
+            alive: parse_color(&self.alive_color)?,
+            dead: parse_color(&self.dead_color)?,
+            dying: self.dying_colors.iter().map(|color| parse_color(color)).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Writes style into a snapshot section
+    pub fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_f64(self.cell_size);
+        encoder.write_str(&self.alive_color);
+        encoder.write_str(&self.dead_color);
+        encoder.write_varint(self.dying_colors.len() as u64);
+
+        for color in &self.dying_colors {
+            encoder.write_str(color);
+        }
+    }
+
+    /// Reads style from a snapshot section
+    pub fn decode(decoder: &mut Decoder) -> Result<UniverseStyle, &'static str> {
+        let cell_size = decoder.read_f64()?;
+
+        if !cell_size.is_finite() || cell_size <= 0.0 {
+            return Err("snapshot holds an invalid cell size");
+        }
+
+        let alive_color = String::from(decoder.read_str()?);
+        let dead_color = String::from(decoder.read_str()?);
+
+        let count = decoder.read_varint()?;
+        let dying_colors = (0..count)
+            .map(|_| decoder.read_str().map(String::from))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UniverseStyle { cell_size, alive_color, dead_color, dying_colors })
+    }
+
+    /// Color of cells in given state
+    pub fn state_color(&self, state: u8) -> &str {
+        match state {
+            0 => &self.dead_color,
+            1 => &self.alive_color,
+            _ => self.dying_colors.get(state as usize - 2)
+                .or(self.dying_colors.last())
+                .unwrap_or(&self.alive_color),
+        }
+    }
 }
 
 impl Default for UniverseStyle {
@@ -64,3 +132,28 @@ impl Default for UniverseStyle {
         UniverseStyle::light()
     }
 }
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(cell_size: f64) -> Result<UniverseStyle, &'static str> {
+        let mut encoder = Encoder::new();
+        UniverseStyle { cell_size, ..UniverseStyle::default() }.encode(&mut encoder);
+
+        let bytes = encoder.into_bytes();
+        UniverseStyle::decode(&mut Decoder::new(&bytes))
+    }
+
+    #[test]
+    fn test_decode() {
+        let style = decode(7.5).unwrap();
+        assert_eq!(style.cell_size, 7.5);
+        assert_eq!(style.alive_color, UniverseStyle::default().alive_color);
+
+        for cell_size in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(decode(cell_size).err(), Some("snapshot holds an invalid cell size"));
+        }
+    }
+}
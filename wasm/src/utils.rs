@@ -1,9 +1,20 @@
-use std::cmp;
-use na::Point2;
-use wasm_bindgen::prelude::wasm_bindgen;
+use js_sys::{Error, Math, Reflect};
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
 
-pub fn cmp_xy_order(lhs: &Point2<i32>, rhs: &Point2<i32>) -> cmp::Ordering {
-    lhs.iter().cmp(rhs.iter())
+/// Generates a seed using js randomness
+pub fn random_seed() -> u64 {
+    (Math::random() * u64::MAX as f64) as u64
+}
+
+/// Converts a pattern parse error into a js error, holding its line and column
+pub fn parse_error(error: gol::ParseError) -> JsValue {
+    let js_error = Error::new(&error.to_string());
+
+    // Setting properties of a fresh error object cannot fail
+    let _ = Reflect::set(&js_error, &JsValue::from_str("line"), &JsValue::from(error.line as u32));
+    let _ = Reflect::set(&js_error, &JsValue::from_str("column"), &JsValue::from(error.column as u32));
+
+    js_error.into()
 }
 
 #[wasm_bindgen]